@@ -0,0 +1,609 @@
+use std::{collections::BTreeMap, fmt};
+
+use base_retro::input::mouse::{Navigate, NavigationAction};
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    focus::{FocusSystems, Focusable},
+    menu::MainMenu,
+    player::{Player, PlayerAction},
+};
+
+pub struct BindingsPlugin;
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Capture>()
+            .configure_sets(Update, FocusSystems.run_if(resource_equals(Capture::Idle)))
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    capture.after(FocusSystems),
+                    (apply_bindings, save_bindings).run_if(resource_changed::<PlayerBindings>),
+                    update_rows,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, close_controls.run_if(navigated_back));
+    }
+}
+
+/// A single key or gamepad button bound to a [`PlayerAction`].
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    fn same_device(&self, other: &Binding) -> bool {
+        matches!(
+            (self, other),
+            (Binding::Key(_), Binding::Key(_)) | (Binding::Gamepad(_), Binding::Gamepad(_))
+        )
+    }
+}
+
+impl From<Binding> for InputKind {
+    fn from(binding: Binding) -> Self {
+        match binding {
+            Binding::Key(key) => key.into(),
+            Binding::Gamepad(button) => button.into(),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Gamepad(button) => write!(f, "Pad {button:?}"),
+        }
+    }
+}
+
+/// The inputs bound to each [`PlayerAction`], loaded before the player
+/// spawns and saved whenever they change.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PlayerBindings(BTreeMap<PlayerAction, Vec<Binding>>);
+
+impl Default for PlayerBindings {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key};
+
+        Self(BTreeMap::from([
+            (
+                PlayerAction::Up,
+                vec![
+                    Key(KeyCode::ArrowUp),
+                    Key(KeyCode::KeyW),
+                    Gamepad(GamepadButtonType::DPadUp),
+                ],
+            ),
+            (
+                PlayerAction::Down,
+                vec![
+                    Key(KeyCode::ArrowDown),
+                    Key(KeyCode::KeyS),
+                    Gamepad(GamepadButtonType::DPadDown),
+                ],
+            ),
+            (
+                PlayerAction::Left,
+                vec![
+                    Key(KeyCode::ArrowLeft),
+                    Key(KeyCode::KeyA),
+                    Gamepad(GamepadButtonType::DPadLeft),
+                ],
+            ),
+            (
+                PlayerAction::Right,
+                vec![
+                    Key(KeyCode::ArrowRight),
+                    Key(KeyCode::KeyD),
+                    Gamepad(GamepadButtonType::DPadRight),
+                ],
+            ),
+            (
+                PlayerAction::Shoot,
+                vec![Key(KeyCode::KeyZ), Key(KeyCode::KeyJ)],
+            ),
+        ]))
+    }
+}
+
+impl PlayerBindings {
+    /// Reads the saved bindings, falling back to the defaults.
+    pub fn load() -> Self {
+        let Some(data) = storage::read() else {
+            return Self::default();
+        };
+
+        ron::from_str(&data).unwrap_or_else(|error| {
+            warn!("ignoring saved bindings: {error}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, default()) {
+            Ok(data) => storage::write(&data),
+            Err(error) => warn!("failed to serialize bindings: {error}"),
+        }
+    }
+
+    pub fn get(&self, action: PlayerAction) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The other action `binding` is already bound to, if any.
+    pub fn conflict(&self, action: PlayerAction, binding: Binding) -> Option<PlayerAction> {
+        self.0
+            .iter()
+            .find(|(other, bindings)| **other != action && bindings.contains(&binding))
+            .map(|(&other, _)| other)
+    }
+
+    /// Replaces the bindings of `action` from the same device as `binding`.
+    pub fn rebind(&mut self, action: PlayerAction, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|other| !other.same_device(&binding));
+        bindings.push(binding);
+    }
+
+    /// The [`InputMap`] for these bindings, with [`PlayerAction::Move`] on the
+    /// left stick and on virtual D-pads made of the direction bindings.
+    pub fn input_map(&self) -> InputMap<PlayerAction> {
+        let mut input_map = InputMap::default();
+        input_map.insert(PlayerAction::Move, DualAxis::left_stick());
+        for dpad in self.move_dpads() {
+            input_map.insert(PlayerAction::Move, dpad);
+        }
+        for (&action, bindings) in &self.0 {
+            for binding in bindings {
+                match *binding {
+                    Binding::Key(key) => input_map.insert(action, key),
+                    Binding::Gamepad(button) => input_map.insert(action, button),
+                };
+            }
+        }
+        input_map
+    }
+
+    /// One [`VirtualDPad`] per keyboard and gamepad binding of the directions,
+    /// pairing the first bindings of each, then the second and so on. Directions
+    /// with fewer bindings repeat their last one, and a device missing a
+    /// direction gets no D-pad.
+    fn move_dpads(&self) -> Vec<VirtualDPad> {
+        let directions = [
+            PlayerAction::Up,
+            PlayerAction::Down,
+            PlayerAction::Left,
+            PlayerAction::Right,
+        ];
+
+        let mut dpads = Vec::new();
+        for keyboard in [true, false] {
+            let inputs = directions.map(|action| {
+                self.get(action)
+                    .iter()
+                    .filter(|binding| matches!(binding, Binding::Key(_)) == keyboard)
+                    .map(|&binding| InputKind::from(binding))
+                    .collect::<Vec<_>>()
+            });
+            if inputs.iter().any(Vec::is_empty) {
+                continue;
+            }
+
+            let count = inputs.iter().map(Vec::len).max().unwrap_or_default();
+            dpads.extend((0..count).map(|index| {
+                let [up, down, left, right] = inputs
+                    .each_ref()
+                    .map(|inputs| inputs[index.min(inputs.len() - 1)]);
+                VirtualDPad {
+                    up,
+                    down,
+                    left,
+                    right,
+                }
+            }));
+        }
+        dpads
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::{fs, path::Path};
+
+    use bevy::log::warn;
+
+    const PATH: &str = "config/bindings.ron";
+
+    pub fn read() -> Option<String> {
+        fs::read_to_string(PATH).ok()
+    }
+
+    pub fn write(data: &str) {
+        let path = Path::new(PATH);
+        if let Err(error) = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, data))
+        {
+            warn!("failed to save bindings to {PATH}: {error}");
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    use bevy::log::warn;
+    use web_sys::Storage;
+
+    const KEY: &str = "bindings";
+
+    fn local_storage() -> Option<Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read() -> Option<String> {
+        local_storage()?.get_item(KEY).ok()?
+    }
+
+    pub fn write(data: &str) {
+        let saved = local_storage().is_some_and(|storage| storage.set_item(KEY, data).is_ok());
+        if !saved {
+            warn!("failed to save bindings to localStorage");
+        }
+    }
+}
+
+/// Whether the controls screen is waiting for the next key or button.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+enum Capture {
+    #[default]
+    Idle,
+    Waiting(PlayerAction),
+}
+
+#[derive(Component)]
+pub struct ControlsScreen;
+
+#[derive(Component)]
+struct BindingRow(PlayerAction);
+
+#[derive(Component)]
+struct BindingStatus;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/x12y12pxMaruMinya.ttf"),
+        font_size: 12.0,
+        color: Color::WHITE,
+    };
+    let button = |width| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(16.0),
+            border: UiRect::all(Val::Px(1.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        border_color: BorderColor(Color::WHITE),
+        background_color: BackgroundColor(Color::BLACK),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            ControlsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(3.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("Controls", style.clone()),
+                Pickable::IGNORE,
+            ));
+
+            for action in PlayerAction::BUTTONS {
+                parent
+                    .spawn((
+                        button(200.0),
+                        BindingRow(action),
+                        Focusable,
+                        On::<Pointer<Click>>::run(
+                            move |mut capture: ResMut<Capture>,
+                                  mut status: Query<&mut Text, With<BindingStatus>>| {
+                                *capture = Capture::Waiting(action);
+                                for mut text in &mut status {
+                                    text.sections[0].value =
+                                        format!("Press a key for {action:?} (Esc cancels)");
+                                }
+                            },
+                        ),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section("", style.clone()),
+                            Pickable::IGNORE,
+                        ));
+                    });
+            }
+
+            parent.spawn((
+                BindingStatus,
+                TextBundle::from_section("", style.clone()),
+                Pickable::IGNORE,
+            ));
+
+            parent
+                .spawn((
+                    button(60.0),
+                    Focusable,
+                    On::<Pointer<Click>>::run(
+                        |mut bindings: ResMut<PlayerBindings>,
+                         mut status: Query<&mut Text, With<BindingStatus>>| {
+                            *bindings = PlayerBindings::default();
+                            for mut text in &mut status {
+                                text.sections[0].value = "Reset to defaults".into();
+                            }
+                        },
+                    ),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section("Reset", style.clone()),
+                        Pickable::IGNORE,
+                    ));
+                });
+
+            parent
+                .spawn((
+                    button(60.0),
+                    Focusable,
+                    On::<Pointer<Click>>::run(close_controls),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((TextBundle::from_section("Back", style), Pickable::IGNORE));
+                });
+        });
+}
+
+/// Shows the controls screen in place of the [`MainMenu`].
+pub fn open_controls(
+    mut screens: Query<&mut Visibility, With<ControlsScreen>>,
+    mut menus: Query<&mut Visibility, (With<MainMenu>, Without<ControlsScreen>)>,
+) {
+    screens
+        .iter_mut()
+        .for_each(|mut visibility| *visibility = Visibility::Inherited);
+    menus
+        .iter_mut()
+        .for_each(|mut visibility| *visibility = Visibility::Hidden);
+}
+
+fn close_controls(
+    mut screens: Query<&mut Visibility, With<ControlsScreen>>,
+    mut menus: Query<&mut Visibility, (With<MainMenu>, Without<ControlsScreen>)>,
+) {
+    screens
+        .iter_mut()
+        .for_each(|mut visibility| *visibility = Visibility::Hidden);
+    menus
+        .iter_mut()
+        .for_each(|mut visibility| *visibility = Visibility::Inherited);
+}
+
+/// The mouse back button leaves the controls screen, unless it is being
+/// bound.
+fn navigated_back(capture: Res<Capture>, mut navigations: EventReader<Navigate>) -> bool {
+    let back = navigations
+        .read()
+        .any(|navigate| navigate.action == NavigationAction::Back);
+    back && *capture == Capture::Idle
+}
+
+/// Binds the next key or gamepad button pressed while [`Capture::Waiting`].
+///
+/// Runs after the focus systems, which are paused while waiting, so the press
+/// that finishes a capture doesn't also move or activate the focus.
+fn capture(
+    mut capture: ResMut<Capture>,
+    mut bindings: ResMut<PlayerBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut status: Query<&mut Text, With<BindingStatus>>,
+) {
+    let Capture::Waiting(action) = *capture else {
+        return;
+    };
+
+    let pressed = keys
+        .get_just_pressed()
+        .map(|&key| Binding::Key(key))
+        .chain(
+            buttons
+                .get_just_pressed()
+                .map(|button| Binding::Gamepad(button.button_type)),
+        )
+        .next();
+    let Some(binding) = pressed else {
+        return;
+    };
+
+    let message = if binding == Binding::Key(KeyCode::Escape) {
+        String::new()
+    } else if let Some(other) = bindings.conflict(action, binding) {
+        format!("{binding} is already bound to {other:?}")
+    } else {
+        bindings.rebind(action, binding);
+        format!("Bound {binding} to {action:?}")
+    };
+
+    for mut text in &mut status {
+        text.sections[0].value.clone_from(&message);
+    }
+    *capture = Capture::Idle;
+}
+
+fn apply_bindings(
+    bindings: Res<PlayerBindings>,
+    mut input_maps: Query<&mut InputMap<PlayerAction>, With<Player>>,
+) {
+    for mut input_map in &mut input_maps {
+        *input_map = bindings.input_map();
+    }
+}
+
+fn save_bindings(bindings: Res<PlayerBindings>) {
+    if !bindings.is_added() {
+        bindings.save();
+    }
+}
+
+fn update_rows(
+    bindings: Res<PlayerBindings>,
+    capture: Res<Capture>,
+    rows: Query<(&BindingRow, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !bindings.is_changed() && !capture.is_changed() {
+        return;
+    }
+
+    for (&BindingRow(action), children) in &rows {
+        let value = if *capture == Capture::Waiting(action) {
+            format!("{action:?}: ...")
+        } else {
+            let inputs: Vec<_> = bindings
+                .get(action)
+                .iter()
+                .map(Binding::to_string)
+                .collect();
+            format!("{action:?}: {}", inputs.join(", "))
+        };
+
+        let mut iter = texts.iter_many_mut(children.iter());
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value.clone_from(&value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_of_other_actions_conflict() {
+        let bindings = PlayerBindings::default();
+
+        assert_eq!(
+            bindings.conflict(PlayerAction::Shoot, Binding::Key(KeyCode::KeyW)),
+            Some(PlayerAction::Up)
+        );
+        assert_eq!(
+            bindings.conflict(PlayerAction::Up, Binding::Key(KeyCode::KeyW)),
+            None
+        );
+        assert_eq!(
+            bindings.conflict(PlayerAction::Shoot, Binding::Key(KeyCode::KeyX)),
+            None
+        );
+    }
+
+    #[test]
+    fn rebinding_replaces_the_same_device() {
+        let mut bindings = PlayerBindings::default();
+
+        bindings.rebind(PlayerAction::Up, Binding::Key(KeyCode::KeyI));
+        assert_eq!(
+            bindings.get(PlayerAction::Up),
+            [
+                Binding::Gamepad(GamepadButtonType::DPadUp),
+                Binding::Key(KeyCode::KeyI),
+            ]
+        );
+
+        bindings.rebind(
+            PlayerAction::Shoot,
+            Binding::Gamepad(GamepadButtonType::South),
+        );
+        assert_eq!(
+            bindings.get(PlayerAction::Shoot),
+            [
+                Binding::Key(KeyCode::KeyZ),
+                Binding::Key(KeyCode::KeyJ),
+                Binding::Gamepad(GamepadButtonType::South),
+            ]
+        );
+    }
+
+    #[test]
+    fn move_follows_the_direction_bindings() {
+        let mut bindings = PlayerBindings::default();
+        assert_eq!(
+            bindings.move_dpads(),
+            [
+                VirtualDPad::arrow_keys(),
+                VirtualDPad::wasd(),
+                VirtualDPad::dpad(),
+            ]
+        );
+
+        bindings.rebind(PlayerAction::Up, Binding::Key(KeyCode::KeyI));
+        bindings.rebind(
+            PlayerAction::Left,
+            Binding::Gamepad(GamepadButtonType::West),
+        );
+        assert_eq!(
+            bindings.move_dpads(),
+            [
+                VirtualDPad {
+                    up: KeyCode::KeyI.into(),
+                    ..VirtualDPad::arrow_keys()
+                },
+                VirtualDPad {
+                    up: KeyCode::KeyI.into(),
+                    ..VirtualDPad::wasd()
+                },
+                VirtualDPad {
+                    left: GamepadButtonType::West.into(),
+                    ..VirtualDPad::dpad()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bindings_round_trip_through_ron() {
+        let mut bindings = PlayerBindings::default();
+        bindings.rebind(PlayerAction::Left, Binding::Key(KeyCode::KeyH));
+        bindings.rebind(
+            PlayerAction::Shoot,
+            Binding::Gamepad(GamepadButtonType::South),
+        );
+
+        let data = ron::ser::to_string_pretty(&bindings, default()).unwrap();
+        assert_eq!(ron::from_str::<PlayerBindings>(&data).unwrap(), bindings);
+    }
+}
@@ -0,0 +1,292 @@
+use base_retro::canvas::{CanvasConfig, CanvasScroll, CanvasScrollSnap};
+use bevy::prelude::*;
+
+use crate::player::Player;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraShake>().add_systems(
+            PostUpdate,
+            (toggle_scroll, add_trauma, follow_player)
+                .chain()
+                .before(CanvasScrollSnap),
+        );
+    }
+}
+
+/// Adds trauma to every [`CanvasCameraController`], the shake grows with the
+/// square of the accumulated trauma.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CameraShake {
+    /// Added trauma, the total is kept between 0 and 1.
+    pub trauma: f32,
+}
+
+impl CameraShake {
+    pub fn new(trauma: f32) -> Self {
+        Self { trauma }
+    }
+}
+
+/// Moves the [`CanvasCamera`](base_retro::canvas::CanvasCamera) it is added to
+/// after the [`Player`], in canvas pixels.
+#[derive(Component, Clone, Debug)]
+pub struct CanvasCameraController {
+    /// Half size of the area around the camera center the player can move in
+    /// without the camera following.
+    pub deadzone: Vec2,
+    /// How far ahead of the player the camera looks in the direction it moves.
+    pub look_ahead: Vec2,
+    /// How quickly the camera catches up, per second. `0.0` follows
+    /// immediately.
+    pub smoothing: f32,
+    /// World area the visible canvas is kept inside of.
+    pub bounds: Option<Rect>,
+    /// Offset at full trauma, rounded to whole pixels.
+    pub max_shake: Vec2,
+    /// How fast the shake wobbles, per second.
+    pub shake_frequency: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    trauma: f32,
+    focus: Option<Vec2>,
+    look: Vec2,
+    last_target: Option<Vec2>,
+}
+
+impl Default for CanvasCameraController {
+    fn default() -> Self {
+        Self {
+            deadzone: Vec2::new(16.0, 12.0),
+            look_ahead: Vec2::new(24.0, 0.0),
+            smoothing: 8.0,
+            bounds: None,
+            max_shake: Vec2::splat(6.0),
+            shake_frequency: 25.0,
+            trauma_decay: 1.5,
+            trauma: 0.0,
+            focus: None,
+            look: Vec2::ZERO,
+            last_target: None,
+        }
+    }
+}
+
+impl CanvasCameraController {
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Returns the shake offset for the current trauma at `time` seconds.
+    fn shake(&self, time: f32) -> Vec2 {
+        let wobble = |phase: f32| {
+            let t = time * self.shake_frequency + phase;
+            (t.sin() + (t * 2.3).sin() * 0.5 + (t * 4.7).sin() * 0.25) / 1.75
+        };
+
+        (self.max_shake * self.trauma.powi(2) * Vec2::new(wobble(0.0), wobble(17.3))).round()
+    }
+
+    /// Moves towards `target` over `delta` seconds, returning the camera
+    /// position with the shake at `time` seconds.
+    fn follow(&mut self, target: Vec2, resolution: Vec2, delta: f32, time: f32) -> Vec2 {
+        let moved = self.last_target.map_or(Vec2::ZERO, |last| target - last);
+        self.last_target = Some(target);
+
+        // Keep looking the same way while standing still.
+        let direction = Vec2::new(
+            if moved.x == 0.0 {
+                0.0
+            } else {
+                moved.x.signum()
+            },
+            if moved.y == 0.0 {
+                0.0
+            } else {
+                moved.y.signum()
+            },
+        );
+        self.look = Vec2::select(
+            direction.cmpeq(Vec2::ZERO),
+            self.look,
+            direction * self.look_ahead,
+        );
+
+        let focus = self.focus.unwrap_or(target);
+        let ahead = target + self.look;
+        let offset = ahead - focus;
+        let mut desired = focus + offset - offset.clamp(-self.deadzone, self.deadzone);
+
+        if let Some(bounds) = self.bounds {
+            let half = resolution / 2.0;
+            let (min, max) = (bounds.min + half, bounds.max - half);
+            desired = Vec2::select(
+                min.cmpgt(max),
+                bounds.center(),
+                desired.clamp(min.min(max), max.max(min)),
+            );
+        }
+
+        let focus = if self.smoothing > 0.0 && self.focus.is_some() {
+            focus.lerp(desired, 1.0 - (-self.smoothing * delta).exp())
+        } else {
+            desired
+        };
+        self.focus = Some(focus);
+
+        focus + self.shake(time)
+    }
+}
+
+/// Scrolls the canvas of cameras with a [`CanvasCameraController`] only, as
+/// scrolling renders the canvas with a margin.
+fn toggle_scroll(
+    mut commands: Commands,
+    added: Query<Entity, (With<CanvasCameraController>, Without<CanvasScroll>)>,
+    mut removed: RemovedComponents<CanvasCameraController>,
+) {
+    for camera in &added {
+        commands.entity(camera).insert(CanvasScroll::default());
+    }
+    for camera in removed.read() {
+        if let Some(mut camera) = commands.get_entity(camera) {
+            camera.remove::<CanvasScroll>();
+        }
+    }
+}
+
+fn add_trauma(
+    time: Res<Time>,
+    mut shakes: EventReader<CameraShake>,
+    mut controllers: Query<&mut CanvasCameraController>,
+) {
+    let trauma: f32 = shakes.read().map(|shake| shake.trauma).sum();
+
+    for mut controller in &mut controllers {
+        let decay = controller.trauma_decay * time.delta_seconds();
+        controller.trauma = (controller.trauma - decay + trauma).clamp(0.0, 1.0);
+    }
+}
+
+fn follow_player(
+    time: Res<Time>,
+    config: Res<CanvasConfig>,
+    players: Query<&Transform, With<Player>>,
+    mut cameras: Query<(&mut CanvasCameraController, &mut CanvasScroll)>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+    let target = player.translation.xy();
+
+    for (mut controller, mut scroll) in &mut cameras {
+        let position = controller.follow(
+            target,
+            config.resolution,
+            time.delta_seconds(),
+            time.elapsed_seconds(),
+        );
+        if scroll.position != position {
+            scroll.position = position;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOLUTION: Vec2 = Vec2::new(320.0, 180.0);
+
+    /// A controller following immediately, without look-ahead.
+    fn controller() -> CanvasCameraController {
+        CanvasCameraController {
+            look_ahead: Vec2::ZERO,
+            smoothing: 0.0,
+            ..default()
+        }
+    }
+
+    fn follow(controller: &mut CanvasCameraController, x: f32, y: f32) -> Vec2 {
+        controller.follow(Vec2::new(x, y), RESOLUTION, 1.0 / 60.0, 0.0)
+    }
+
+    #[test]
+    fn camera_follows_out_of_the_deadzone() {
+        let mut controller = controller();
+        assert_eq!(follow(&mut controller, 0.0, 0.0), Vec2::ZERO);
+
+        assert_eq!(follow(&mut controller, 10.0, -12.0), Vec2::ZERO);
+        assert_eq!(follow(&mut controller, 20.0, -12.0), Vec2::new(4.0, 0.0));
+        assert_eq!(follow(&mut controller, 20.0, -20.0), Vec2::new(4.0, -8.0));
+        // Turning around moves the player through the deadzone first.
+        assert_eq!(follow(&mut controller, 0.0, -20.0), Vec2::new(4.0, -8.0));
+        assert_eq!(follow(&mut controller, -20.0, -20.0), Vec2::new(-4.0, -8.0));
+    }
+
+    #[test]
+    fn camera_looks_ahead_of_the_movement() {
+        let mut controller = CanvasCameraController {
+            deadzone: Vec2::ZERO,
+            look_ahead: Vec2::new(24.0, 8.0),
+            ..controller()
+        };
+        assert_eq!(follow(&mut controller, 0.0, 0.0), Vec2::ZERO);
+
+        assert_eq!(follow(&mut controller, 1.0, 0.0), Vec2::new(25.0, 0.0));
+        // Standing still keeps looking the same way.
+        assert_eq!(follow(&mut controller, 1.0, 0.0), Vec2::new(25.0, 0.0));
+        assert_eq!(follow(&mut controller, 0.0, 1.0), Vec2::new(-24.0, 9.0));
+    }
+
+    #[test]
+    fn bounds_keep_the_canvas_inside() {
+        let mut controller = CanvasCameraController {
+            bounds: Some(Rect::new(-200.0, -100.0, 200.0, 100.0)),
+            deadzone: Vec2::ZERO,
+            ..controller()
+        };
+        assert_eq!(follow(&mut controller, 0.0, 0.0), Vec2::ZERO);
+        assert_eq!(follow(&mut controller, 100.0, 50.0), Vec2::new(40.0, 10.0));
+        assert_eq!(
+            follow(&mut controller, -300.0, -300.0),
+            Vec2::new(-40.0, -10.0)
+        );
+
+        // Bounds smaller than the canvas center it.
+        controller.bounds = Some(Rect::new(0.0, 0.0, 100.0, 400.0));
+        assert_eq!(
+            follow(&mut controller, 500.0, 500.0),
+            Vec2::new(50.0, 310.0)
+        );
+    }
+
+    #[test]
+    fn shake_is_rounded_to_whole_pixels() {
+        let mut controller = controller();
+        assert_eq!(controller.shake(0.3), Vec2::ZERO);
+
+        controller.trauma = 0.5;
+        let shakes: Vec<_> = (0..100)
+            .map(|i| controller.shake(i as f32 * 0.01))
+            .collect();
+        for shake in &shakes {
+            assert_eq!(*shake, shake.round());
+            assert!(
+                shake
+                    .abs()
+                    .cmple((controller.max_shake / 4.0).round())
+                    .all(),
+                "{shake}"
+            );
+        }
+        assert!(shakes.iter().any(|shake| *shake != Vec2::ZERO));
+
+        controller.trauma = 1.0;
+        let position = controller.follow(Vec2::ZERO, RESOLUTION, 1.0 / 60.0, 0.37);
+        assert_eq!(position, position.round());
+        assert_eq!(position, controller.shake(0.37));
+    }
+}
@@ -0,0 +1,57 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base_retro::canvas::{CanvasCapture, CanvasCapturePlugin, ClipFormat};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            CanvasCapturePlugin,
+            InputManagerPlugin::<CaptureAction>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, capture);
+    }
+}
+
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+enum CaptureAction {
+    Screenshot,
+    ToggleRecording,
+    SaveClip,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(InputManagerBundle::with_map(InputMap::new([
+        (CaptureAction::Screenshot, KeyCode::F12),
+        (CaptureAction::ToggleRecording, KeyCode::F10),
+        (CaptureAction::SaveClip, KeyCode::F11),
+    ])));
+}
+
+fn capture(mut capture: ResMut<CanvasCapture>, action_state: Query<&ActionState<CaptureAction>>) {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis());
+
+    for action in &action_state {
+        if action.just_pressed(&CaptureAction::Screenshot) {
+            capture.screenshot(format!("captures/{stamp}.png"), 1);
+        }
+        if action.just_pressed(&CaptureAction::ToggleRecording) {
+            if capture.is_recording() {
+                capture.stop_recording();
+                info!("Stopped recording the canvas");
+            } else {
+                capture.start_recording(5.0, 20.0);
+                info!("Recording the last 5 seconds of the canvas");
+            }
+        }
+        if action.just_pressed(&CaptureAction::SaveClip) {
+            capture.save_recording(format!("captures/{stamp}.gif"), ClipFormat::Gif, 2);
+        }
+    }
+}
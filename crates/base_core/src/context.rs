@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    plugin::{InputManagerSystem, ToggleActions},
+    prelude::*,
+};
+
+/// Who is reading input, so menus and gameplay don't act on the same keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum InputContext {
+    Gameplay,
+    Menu,
+    Dialogue,
+    Console,
+}
+
+/// The stack of [`InputContext`]s, from the bottom up. Only the actions
+/// registered for the top context read input, actions not registered for
+/// any context always do.
+#[derive(Resource, Debug)]
+pub struct InputContexts(Vec<InputContext>);
+
+impl Default for InputContexts {
+    fn default() -> Self {
+        Self(vec![InputContext::Gameplay])
+    }
+}
+
+impl InputContexts {
+    /// The context reading input.
+    pub fn top(&self) -> InputContext {
+        self.0.last().copied().unwrap_or(InputContext::Gameplay)
+    }
+
+    pub fn is_active(&self, context: InputContext) -> bool {
+        self.top() == context
+    }
+
+    pub fn push(&mut self, context: InputContext) {
+        self.0.push(context);
+    }
+
+    /// Removes the topmost `context`, wherever it is in the stack.
+    pub fn remove(&mut self, context: InputContext) {
+        if let Some(index) = self.0.iter().rposition(|&other| other == context) {
+            self.0.remove(index);
+        }
+    }
+}
+
+/// Systems toggling the actions of each context, before the input manager
+/// updates them.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputContextSystems;
+
+pub trait InputContextApp {
+    /// Updates the `A` actions from input only while `context` is on top.
+    fn add_input_context<A: Actionlike>(&mut self, context: InputContext) -> &mut Self;
+
+    /// Pushes `context` while in `state`.
+    fn add_state_input_context<S: States>(&mut self, state: S, context: InputContext) -> &mut Self;
+}
+
+impl InputContextApp for App {
+    fn add_input_context<A: Actionlike>(&mut self, context: InputContext) -> &mut Self {
+        self.init_resource::<InputContexts>()
+            .configure_sets(
+                PreUpdate,
+                InputContextSystems.before(InputManagerSystem::Update),
+            )
+            .add_systems(
+                PreUpdate,
+                toggle_actions::<A>(context).in_set(InputContextSystems),
+            )
+    }
+
+    fn add_state_input_context<S: States>(&mut self, state: S, context: InputContext) -> &mut Self {
+        self.init_resource::<InputContexts>()
+            .add_systems(
+                OnEnter(state.clone()),
+                move |mut contexts: ResMut<InputContexts>| contexts.push(context),
+            )
+            .add_systems(OnExit(state), move |mut contexts: ResMut<InputContexts>| {
+                contexts.remove(context)
+            })
+    }
+}
+
+/// Skips the input manager updates of the `A` actions while `context` is
+/// covered. The input manager releases them when they are toggled off, so
+/// held actions don't stay pressed meanwhile.
+fn toggle_actions<A: Actionlike>(
+    context: InputContext,
+) -> impl FnMut(Res<InputContexts>, ResMut<ToggleActions<A>>) {
+    move |contexts, mut toggle| {
+        let enabled = contexts.is_active(context);
+        if toggle.enabled != enabled {
+            toggle.enabled = enabled;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::InputPlugin;
+
+    use super::*;
+
+    #[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+    enum TestAction {
+        Jump,
+    }
+
+    #[test]
+    fn contexts_stack() {
+        let mut contexts = InputContexts::default();
+        assert!(contexts.is_active(InputContext::Gameplay));
+
+        contexts.push(InputContext::Menu);
+        contexts.push(InputContext::Dialogue);
+        assert_eq!(contexts.top(), InputContext::Dialogue);
+
+        contexts.remove(InputContext::Dialogue);
+        assert_eq!(contexts.top(), InputContext::Menu);
+        contexts.remove(InputContext::Menu);
+        assert_eq!(contexts.top(), InputContext::Gameplay);
+    }
+
+    #[test]
+    fn contexts_are_removed_from_the_middle() {
+        let mut contexts = InputContexts::default();
+        contexts.push(InputContext::Menu);
+        contexts.push(InputContext::Console);
+
+        contexts.remove(InputContext::Menu);
+        assert_eq!(contexts.top(), InputContext::Console);
+        contexts.remove(InputContext::Console);
+        assert_eq!(contexts.top(), InputContext::Gameplay);
+
+        // Removing a context that isn't there leaves the stack alone.
+        contexts.remove(InputContext::Dialogue);
+        assert_eq!(contexts.0, [InputContext::Gameplay]);
+    }
+
+    #[test]
+    fn covered_contexts_release_their_actions() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .add_plugins(InputManagerPlugin::<TestAction>::default())
+            .add_input_context::<TestAction>(InputContext::Gameplay);
+        let entity = app
+            .world_mut()
+            .spawn(InputManagerBundle::with_map(InputMap::new([(
+                TestAction::Jump,
+                KeyCode::Space,
+            )])))
+            .id();
+        let action = |app: &App| {
+            app.world()
+                .get::<ActionState<TestAction>>(entity)
+                .unwrap()
+                .clone()
+        };
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        app.update();
+        assert!(action(&app).pressed(&TestAction::Jump));
+
+        app.world_mut()
+            .resource_mut::<InputContexts>()
+            .push(InputContext::Menu);
+        app.update();
+        assert!(action(&app).released(&TestAction::Jump));
+        app.update();
+        assert!(action(&app).released(&TestAction::Jump));
+
+        app.world_mut()
+            .resource_mut::<InputContexts>()
+            .remove(InputContext::Menu);
+        app.update();
+        assert!(action(&app).pressed(&TestAction::Jump));
+    }
+}
@@ -1,90 +1,417 @@
+use std::borrow::Cow;
+
+use anyhow::bail;
 use base_config::prelude::*;
-use base_retro::{canvas::CanvasSetup, prelude::*};
-use bevy::{prelude::*, sprite::Anchor};
+use base_retro::{canvas::CanvasSetup, input::gamepad::PointerSnap, prelude::*};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext},
+    ecs::system::EntityCommands,
+    prelude::*,
+    sprite::Anchor,
+    utils::HashMap,
+};
+use bevy_aseprite_ultra::{
+    prelude::{Animation, AnimationRepeat, Aseprite, AsepriteAnimationBundle},
+    BevySprityPlugin, NotLoaded,
+};
 use bevy_mod_picking::{
     pointer::{InputMove, InputPress, Location, PressDirection},
     prelude::*,
 };
+use serde::Deserialize;
 
 pub struct CursorPlugin;
 
 impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CursorConfig>()
+        if !app.is_plugin_added::<BevySprityPlugin>() {
+            app.add_plugins(BevySprityPlugin);
+        }
+
+        app.init_asset::<CursorTheme>()
+            .init_asset_loader::<CursorThemeLoader>()
+            .init_resource::<CursorConfig>()
             .add_systems(Startup, setup.after(CanvasSetup))
-            .add_systems(Update, (update_position, update_icon, apply_icon));
+            .add_systems(
+                Update,
+                (
+                    (spawn_cursors, despawn_cursors, apply_tints).chain(),
+                    (
+                        update_position,
+                        (setup_draggables, update_icon).chain(),
+                        (apply_themes, apply_icon, play_click).chain(),
+                    ),
+                )
+                    .chain(),
+            );
     }
 }
 
+/// The cursor sprite of the pointer entity `pointer`.
 #[derive(Component)]
-struct Cursor;
+struct Cursor {
+    pointer: Entity,
+    pointer_id: PointerId,
+}
+
+/// Tints the cursor of the pointer entity this is on, to tell players apart.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CursorTint(pub Color);
 
 #[derive(Component)]
 struct CursorCamera;
 
-#[derive(Component, Default, PartialEq)]
-pub enum CursorState {
-    #[default]
-    Arrow,
-    Grab,
-    Grabbing,
-    Crosshair,
-}
+/// The sprite last applied to a cursor, so it's only replaced, and its
+/// animation restarted, when the sprite of its state changes.
+#[derive(Component)]
+struct ShownSprite(CursorSprite);
+
+/// Name of the sprite the cursor shows, registered in the [`CursorConfig`].
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CursorState(Cow<'static, str>);
 
 impl CursorState {
-    fn get_data<'a>(&self, config: &'a CursorConfig) -> &'a CursorSprite {
-        match self {
-            CursorState::Arrow => &config.arrow,
-            CursorState::Grab => &config.grab,
-            CursorState::Grabbing => &config.grabbing,
-            CursorState::Crosshair => &config.crosshair,
-        }
+    pub const ARROW: CursorState = CursorState::from_static("Arrow");
+    pub const GRAB: CursorState = CursorState::from_static("Grab");
+    pub const GRABBING: CursorState = CursorState::from_static("Grabbing");
+    pub const CROSSHAIR: CursorState = CursorState::from_static("Crosshair");
+
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+
+    pub const fn from_static(name: &'static str) -> Self {
+        Self(Cow::Borrowed(name))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for CursorState {
+    fn default() -> Self {
+        Self::ARROW
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct CursorSprite {
-    texture: Handle<Image>,
-    anchor: Anchor,
+    pub texture: Handle<Image>,
+    pub anchor: Anchor,
+    /// Pixel of the texture, from its top left corner, that points at the
+    /// cursor position. Replaces the `anchor` once the texture is loaded.
+    /// Not used by animated sprites.
+    pub hotspot: Option<Vec2>,
+    /// Shown instead of the `texture` when set.
+    pub animation: Option<CursorAnimation>,
 }
 
+impl CursorSprite {
+    pub fn new(texture: Handle<Image>, anchor: Anchor) -> Self {
+        Self {
+            texture,
+            anchor,
+            hotspot: None,
+            animation: None,
+        }
+    }
+
+    pub fn animated(animation: CursorAnimation, anchor: Anchor) -> Self {
+        Self {
+            texture: Handle::default(),
+            anchor,
+            hotspot: None,
+            animation: Some(animation),
+        }
+    }
+
+    pub fn with_hotspot(mut self, hotspot: Vec2) -> Self {
+        self.hotspot = Some(hotspot);
+        self
+    }
+
+    fn resolve_anchor(&self, images: &Assets<Image>) -> Anchor {
+        let size = images.get(&self.texture).map(|image| image.size_f32());
+        match (self.hotspot, size) {
+            (Some(hotspot), Some(size)) => {
+                // Anchors grow up from the center, hotspots down from the corner.
+                Anchor::Custom(Vec2::new(
+                    hotspot.x / size.x - 0.5,
+                    0.5 - hotspot.y / size.y,
+                ))
+            }
+            _ => self.anchor,
+        }
+    }
+}
+
+/// An Aseprite animation looping while the cursor is in a state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorAnimation {
+    pub aseprite: Handle<Aseprite>,
+    /// Tag looped, or every frame when `None`.
+    pub tag: Option<String>,
+    /// Tag played once when a pointer is pressed, before looping `tag` again.
+    /// Stays on its last frame without a `tag`.
+    pub click_tag: Option<String>,
+}
+
+impl CursorAnimation {
+    pub fn new(aseprite: Handle<Aseprite>) -> Self {
+        Self {
+            aseprite,
+            tag: None,
+            click_tag: None,
+        }
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn with_click_tag(mut self, tag: impl Into<String>) -> Self {
+        self.click_tag = Some(tag.into());
+        self
+    }
+
+    fn looping(&self) -> Animation {
+        let animation = Animation::default().with_repeat(AnimationRepeat::Loop);
+        match &self.tag {
+            Some(tag) => animation.with_tag(tag),
+            None => animation,
+        }
+    }
+}
+
+/// The sprites of every [`CursorState`], by name.
+///
+/// States are added with [`CursorConfig::register`], or from the `.cursors.ron`
+/// themes loaded with [`CursorConfig::load_theme`]. Unknown states show the
+/// [`CursorState::ARROW`] sprite.
 #[derive(Resource)]
 pub struct CursorConfig {
-    arrow: CursorSprite,
-    grab: CursorSprite,
-    grabbing: CursorSprite,
-    crosshair: CursorSprite,
+    sprites: HashMap<CursorState, CursorSprite>,
+    themes: Vec<Handle<CursorTheme>>,
+    /// Whether touch pointers show their cursor, fingers already show where
+    /// they are.
+    pub show_touch_cursors: bool,
+}
+
+impl CursorConfig {
+    /// Adds or replaces the sprite of `state`.
+    pub fn register(&mut self, state: CursorState, sprite: CursorSprite) {
+        self.sprites.insert(state, sprite);
+    }
+
+    pub fn get(&self, state: &CursorState) -> Option<&CursorSprite> {
+        self.sprites
+            .get(state)
+            .or_else(|| self.sprites.get(&CursorState::ARROW))
+    }
+
+    /// Registers every state of the theme at `path` once it is loaded, and
+    /// again whenever it is modified.
+    pub fn load_theme(&mut self, asset_server: &AssetServer, path: impl Into<AssetPath<'static>>) {
+        self.themes.push(asset_server.load(path.into()));
+    }
 }
 
 impl FromWorld for CursorConfig {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
-        Self {
-            arrow: CursorSprite {
-                texture: asset_server.load("arrow.png"),
-                anchor: Anchor::Custom(Vec2::new(-0.3, 0.5)),
-            },
-            grab: CursorSprite {
-                texture: asset_server.load("grab.png"),
-                anchor: Anchor::Custom(Vec2::new(-0.1, 0.2)),
-            },
-            grabbing: CursorSprite {
-                texture: asset_server.load("grabbing.png"),
-                anchor: Anchor::Custom(Vec2::new(-0.1, 0.2)),
-            },
-            crosshair: CursorSprite {
-                texture: asset_server.load("crosshair.png"),
-                anchor: Anchor::Center,
-            },
-        }
+        let mut config = Self {
+            sprites: HashMap::new(),
+            themes: Vec::new(),
+            show_touch_cursors: false,
+        };
+        config.register(
+            CursorState::ARROW,
+            CursorSprite::new(
+                asset_server.load("arrow.png"),
+                Anchor::Custom(Vec2::new(-0.3, 0.5)),
+            ),
+        );
+        config.register(
+            CursorState::GRAB,
+            CursorSprite::new(
+                asset_server.load("grab.png"),
+                Anchor::Custom(Vec2::new(-0.1, 0.2)),
+            ),
+        );
+        config.register(
+            CursorState::GRABBING,
+            CursorSprite::new(
+                asset_server.load("grabbing.png"),
+                Anchor::Custom(Vec2::new(-0.1, 0.2)),
+            ),
+        );
+        config.register(
+            CursorState::CROSSHAIR,
+            CursorSprite::new(asset_server.load("crosshair.png"), Anchor::Center),
+        );
+        config
+    }
+}
+
+/// Cursor sprites by state name, loaded from `.cursors.ron` files like
+///
+/// ```ron
+/// {
+///     "Forbidden": (sprite: "cursors/forbidden.png", hotspot: Some((7, 7))),
+///     "Talk": (sprite: "cursors/talk.png", anchor: (-0.5, 0.5)),
+///     "Busy": (aseprite: "cursors/busy.aseprite", tag: Some("spin")),
+///     "Attack": (aseprite: "cursors/sword.aseprite", click_tag: Some("slash")),
+/// }
+/// ```
+///
+/// Each state has either a `sprite` or an `aseprite`. Anchors default to the
+/// center of the sprite.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct CursorTheme {
+    pub sprites: HashMap<String, CursorSprite>,
+}
+
+#[derive(Deserialize)]
+struct CursorThemeEntry {
+    #[serde(default)]
+    sprite: Option<String>,
+    #[serde(default)]
+    aseprite: Option<String>,
+    #[serde(default)]
+    tag: Option<String>,
+    #[serde(default)]
+    click_tag: Option<String>,
+    #[serde(default)]
+    anchor: Vec2,
+    #[serde(default)]
+    hotspot: Option<Vec2>,
+}
+
+#[derive(Default)]
+pub struct CursorThemeLoader;
+
+impl AssetLoader for CursorThemeLoader {
+    type Asset = CursorTheme;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<CursorTheme, Self::Error> {
+        let mut source = String::new();
+        reader.read_to_string(&mut source).await?;
+        let entries: HashMap<String, CursorThemeEntry> = ron::from_str(&source)?;
+
+        let sprites = entries
+            .into_iter()
+            .map(|(name, entry)| {
+                let anchor = Anchor::Custom(entry.anchor);
+                let mut sprite = match (entry.sprite, entry.aseprite) {
+                    (Some(sprite), None) => CursorSprite::new(load_context.load(sprite), anchor),
+                    (None, Some(aseprite)) => CursorSprite::animated(
+                        CursorAnimation {
+                            aseprite: load_context.load(aseprite),
+                            tag: entry.tag,
+                            click_tag: entry.click_tag,
+                        },
+                        anchor,
+                    ),
+                    _ => bail!("cursor `{name}` needs either a `sprite` or an `aseprite`"),
+                };
+                sprite.hotspot = entry.hotspot;
+                Ok((name, sprite))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(CursorTheme { sprites })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cursors.ron"]
     }
 }
 
+/// The cursor state shown while the pointer is over this entity or its
+/// descendants.
+///
+/// The innermost hint wins, unless an ancestor's hint has a higher
+/// [`CursorHintPriority`].
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct CursorHint(pub CursorState);
+
+/// Priority of the [`CursorHint`] on the same entity, `0` without one.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CursorHintPriority(pub i32);
+
+/// Can be grabbed, showing [`CursorState::GRAB`] on hover and
+/// [`CursorState::GRABBING`] while pressed. Adds that hint unless the entity
+/// already has one, and a [`PointerSnap`] for the gamepad pointer.
+///
+/// Moved by the pointer with the [`DragPlugin`](crate::drag::DragPlugin).
 #[derive(Component)]
 pub struct Draggable;
 
+fn setup_draggables(
+    mut commands: Commands,
+    draggables: Query<(Entity, Has<CursorHint>), Added<Draggable>>,
+) {
+    for (entity, hinted) in &draggables {
+        let mut entity = commands.entity(entity);
+        entity.insert(PointerSnap);
+        if !hinted {
+            entity.insert(CursorHint(CursorState::GRAB));
+        }
+    }
+}
+
+type HintQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static CursorHint>,
+        Option<&'static CursorHintPriority>,
+        Option<&'static Parent>,
+    ),
+>;
+
+pub(crate) type DraggableQuery<'w, 's> = Query<'w, 's, (Has<Draggable>, Option<&'static Parent>)>;
+
+/// The nearest [`Draggable`] among `entity` and its ancestors, picking hits
+/// the innermost entity, which may be part of a draggable.
+pub(crate) fn find_draggable(mut entity: Entity, draggables: &DraggableQuery) -> Option<Entity> {
+    loop {
+        let (draggable, parent) = draggables.get(entity).ok()?;
+        if draggable {
+            return Some(entity);
+        }
+        entity = parent?.get();
+    }
+}
+
+/// The hint applying to `entity`, and the entity it is on.
+fn find_hint<'a>(mut entity: Entity, hints: &'a HintQuery) -> Option<(Entity, &'a CursorHint)> {
+    let mut found: Option<(Entity, &CursorHint, CursorHintPriority)> = None;
+    while let Ok((hint, priority, parent)) = hints.get(entity) {
+        if let Some(hint) = hint {
+            let priority = priority.copied().unwrap_or_default();
+            if found.is_none_or(|(_, _, best)| priority > best) {
+                found = Some((entity, hint, priority));
+            }
+        }
+        let Some(parent) = parent else {
+            break;
+        };
+        entity = parent.get();
+    }
+    found.map(|(entity, hint, _)| (entity, hint))
+}
+
 fn setup(
     mut commands: Commands,
-    config: Res<CursorConfig>,
     mut windows: Query<&mut Window>,
     camera: Query<&Camera, With<CanvasCamera>>,
 ) {
@@ -104,38 +431,102 @@ fn setup(
             ..default()
         },
     ));
+}
 
-    let state = CursorState::default();
-    let cursor = state.get_data(config.as_ref());
-    commands.spawn((
-        Cursor,
-        state,
-        SpriteBundle {
-            sprite: Sprite {
-                anchor: cursor.anchor,
+/// Spawns a cursor for every new pointer.
+fn spawn_cursors(
+    mut commands: Commands,
+    pointers: Query<(Entity, &PointerId, Option<&CursorTint>), Added<PointerId>>,
+) {
+    for (pointer, &pointer_id, tint) in &pointers {
+        // Other pointers, like the gamepad one, exist from the start but only
+        // show once they move.
+        let visibility = if pointer_id.is_mouse() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        // The sprite is set by `apply_icon` once the state is added.
+        commands.spawn((
+            Cursor {
+                pointer,
+                pointer_id,
+            },
+            CursorState::default(),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: tint.map_or(Color::WHITE, |tint| tint.0),
+                    ..default()
+                },
+                visibility,
                 ..default()
             },
-            texture: cursor.texture.clone(),
-            ..default()
-        },
-        Pickable::IGNORE,
-        POINTER_LAYER,
-    ));
+            Pickable::IGNORE,
+            POINTER_LAYER,
+        ));
+    }
+}
+
+fn despawn_cursors(
+    mut commands: Commands,
+    mut removed: RemovedComponents<PointerId>,
+    cursors: Query<(Entity, &Cursor)>,
+) {
+    for pointer in removed.read() {
+        for (entity, _) in cursors
+            .iter()
+            .filter(|(_, cursor)| cursor.pointer == pointer)
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn apply_tints(
+    tints: Query<(Entity, &CursorTint), Changed<CursorTint>>,
+    mut cursors: Query<(&Cursor, &mut Sprite)>,
+) {
+    for (pointer, tint) in &tints {
+        for (_, mut sprite) in cursors
+            .iter_mut()
+            .filter(|(cursor, _)| cursor.pointer == pointer)
+        {
+            sprite.color = tint.0;
+        }
+    }
 }
 
 fn update_position(
-    mut cursor_query: Query<&mut Transform, With<Cursor>>,
+    config: Res<CursorConfig>,
+    mut cursors: Query<(&Cursor, &mut Transform, &mut Visibility)>,
+    camera: Query<(&Camera, &GlobalTransform), With<CursorCamera>>,
     mut input_move: EventReader<InputMove>,
 ) {
-    let mut cursor = cursor_query.single_mut();
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
 
     for InputMove {
+        pointer_id,
         location: Location { position, .. },
         ..
     } in input_move.read()
     {
-        cursor.translation.x = position.x;
-        cursor.translation.y = position.y;
+        let shown = !pointer_id.is_touch() || config.show_touch_cursors;
+        let position = camera.viewport_to_world_2d(camera_transform, *position);
+        for (_, mut transform, mut visibility) in cursors
+            .iter_mut()
+            .filter(|(cursor, ..)| cursor.pointer_id == *pointer_id)
+        {
+            if shown {
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            if let Some(position) = position {
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+            }
+        }
     }
 }
 
@@ -144,83 +535,370 @@ fn update_icon(
     mut input_move: EventReader<InputMove>,
     mut input_press: EventReader<InputPress>,
     pointer_map: Res<PointerMap>,
-    mut current_drag: Local<Option<Entity>>,
-    draggable: Query<(Has<Draggable>, Option<&Parent>)>,
+    mut current_drags: Local<HashMap<PointerId, Entity>>,
+    hints: HintQuery,
+    draggables: DraggableQuery,
     pointer_query: Query<&PointerInteraction>,
-    mut states: Query<&mut CursorState, With<Cursor>>,
+    mut states: Query<(&Cursor, &mut CursorState)>,
 ) {
-    let mut icon = states.single_mut();
+    let mut set_state = |pointer_id, state: CursorState| {
+        for (_, mut icon) in states
+            .iter_mut()
+            .filter(|(cursor, _)| cursor.pointer_id == pointer_id)
+        {
+            icon.set_if_neq(state.clone());
+        }
+    };
+    let (hints, pointer_map, pointer_query) = (&hints, &pointer_map, &pointer_query);
+    let nearest_hit = move |pointer_id| {
+        let pointer_entity = pointer_map.get_entity(pointer_id)?;
+        let interaction = pointer_query.get(pointer_entity).ok()?;
+        interaction.get_nearest_hit().map(|&(entity, _)| entity)
+    };
+    let hover_state = |pointer_id| {
+        nearest_hit(pointer_id)
+            .and_then(|entity| find_hint(entity, hints))
+            .map_or(CursorState::ARROW, |(_, hint)| hint.0.clone())
+    };
 
     for event in input_move.read() {
-        let pointer_entity = pointer_map.get_entity(event.pointer_id).unwrap();
-        let interaction = pointer_query.get(pointer_entity).unwrap();
-
-        let mut hover = false;
-        if let Some(&(mut entity, _)) = interaction.get_nearest_hit() {
-            while let Ok((draggable, parent)) = draggable.get(entity) {
-                if draggable {
-                    if current_drag.is_none() {
-                        icon.set_if_neq(CursorState::Grab);
-                    }
-                    hover = true;
-                    break;
-                }
+        if !current_drags.contains_key(&event.pointer_id) {
+            set_state(event.pointer_id, hover_state(event.pointer_id));
+        }
+    }
 
-                if let Some(parent) = parent {
-                    entity = parent.get();
-                } else {
-                    break;
+    for event in input_press.read() {
+        match event.direction {
+            PressDirection::Down => {
+                // Grabs whatever draggable is pressed, whichever hint shows
+                // over it.
+                let draggable = nearest_hit(event.pointer_id)
+                    .and_then(|entity| find_draggable(entity, &draggables));
+                if let Some(entity) = draggable {
+                    current_drags.insert(event.pointer_id, entity);
+                    set_state(event.pointer_id, CursorState::GRABBING);
                 }
             }
+            PressDirection::Up => {
+                current_drags.remove(&event.pointer_id);
+                set_state(event.pointer_id, hover_state(event.pointer_id));
+            }
         }
+    }
+}
+
+fn apply_themes(
+    mut config: ResMut<CursorConfig>,
+    mut theme_events: EventReader<AssetEvent<CursorTheme>>,
+    themes: Res<Assets<CursorTheme>>,
+) {
+    for event in theme_events.read() {
+        let Some(theme) = config
+            .themes
+            .iter()
+            .find(|theme| event.is_loaded_with_dependencies(*theme) || event.is_modified(*theme))
+            .and_then(|theme| themes.get(theme))
+        else {
+            continue;
+        };
 
-        if current_drag.is_none() && !hover {
-            icon.set_if_neq(CursorState::Arrow);
+        for (name, sprite) in &theme.sprites {
+            config.register(CursorState::new(name.clone()), sprite.clone());
         }
     }
+}
 
-    for event in input_press.read() {
-        if let PressDirection::Up = event.direction {
-            *current_drag = None;
-            icon.set_if_neq(CursorState::Arrow);
-        };
+#[allow(clippy::type_complexity)]
+fn apply_icon(
+    mut commands: Commands,
+    config: Res<CursorConfig>,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut cursors: Query<
+        (
+            Entity,
+            &CursorState,
+            Option<&ShownSprite>,
+            &mut Handle<Image>,
+            &mut Sprite,
+        ),
+        With<Cursor>,
+    >,
+) {
+    let image_events: Vec<_> = image_events.read().collect();
 
-        let pointer_entity = pointer_map.get_entity(event.pointer_id).unwrap();
-        let interaction = pointer_query.get(pointer_entity).unwrap();
-
-        if let Some(&(mut entity, _)) = interaction.get_nearest_hit() {
-            while let Ok((draggable, parent)) = draggable.get(entity) {
-                if draggable {
-                    icon.set_if_neq(if let PressDirection::Down = event.direction {
-                        *current_drag = Some(entity);
-                        CursorState::Grabbing
-                    } else {
-                        *current_drag = None;
-                        CursorState::Grab
-                    });
-                    break;
-                }
+    for (entity, state, shown, mut handle, mut sprite) in &mut cursors {
+        let Some(cursor) = config.get(state) else {
+            continue;
+        };
+        let changed = shown.is_none_or(|ShownSprite(shown)| shown != cursor);
+        // Hotspots only resolve to an anchor once the texture size is known.
+        let loaded = image_events
+            .iter()
+            .any(|event| event.is_loaded_with_dependencies(&cursor.texture));
+        if changed || loaded {
+            sprite.anchor = cursor.resolve_anchor(&images);
+        }
+        if !changed {
+            continue;
+        }
 
-                if let Some(parent) = parent {
-                    entity = parent.get();
-                } else {
-                    break;
-                }
+        let mut entity = commands.entity(entity);
+        entity.insert(ShownSprite(cursor.clone()));
+        match &cursor.animation {
+            Some(animation) => {
+                entity.insert(animation_components(
+                    animation.aseprite.clone(),
+                    animation.looping(),
+                ));
+            }
+            None => {
+                *handle = cursor.texture.clone();
+                remove_bundle(&mut entity, animation_components(default(), default()));
             }
         }
     }
 }
 
-#[allow(clippy::type_complexity)]
-fn apply_icon(
+/// The components an [`AsepriteAnimationBundle`] adds on top of a sprite,
+/// loading the animation from its first frame. Its state component isn't
+/// exported, so it is taken from the bundle.
+fn animation_components(aseprite: Handle<Aseprite>, animation: Animation) -> impl Bundle {
+    let AsepriteAnimationBundle {
+        animation_state, ..
+    } = default();
+    (
+        aseprite,
+        animation,
+        animation_state,
+        TextureAtlas::default(),
+        NotLoaded,
+    )
+}
+
+fn remove_bundle<B: Bundle>(entity: &mut EntityCommands, _: B) {
+    entity.remove::<B>();
+}
+
+/// Plays the click animation of the cursor state on pointer presses.
+fn play_click(
     config: Res<CursorConfig>,
-    mut pointer: Query<(Ref<CursorState>, &mut Handle<Image>, &mut Sprite), With<Cursor>>,
+    mut input_press: EventReader<InputPress>,
+    mut cursors: Query<(&Cursor, &CursorState, &mut Animation)>,
 ) {
-    let (state, mut handle, mut sprite) = pointer.single_mut();
-    if !state.is_changed() {
-        return;
+    for event in input_press.read() {
+        if !matches!(event.direction, PressDirection::Down) {
+            continue;
+        }
+
+        for (_, state, mut animation) in cursors
+            .iter_mut()
+            .filter(|(cursor, ..)| cursor.pointer_id == event.pointer_id)
+        {
+            let Some(cursor) = config
+                .get(state)
+                .and_then(|cursor| cursor.animation.as_ref())
+            else {
+                continue;
+            };
+            let Some(click_tag) = &cursor.click_tag else {
+                continue;
+            };
+
+            // Starting over drops the loop queued by earlier clicks.
+            *animation = cursor.looping();
+            animation.play(click_tag, AnimationRepeat::Count(1));
+            if let Some(tag) = &cursor.tag {
+                animation.then(tag, AnimationRepeat::Loop);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_retro::{
+        canvas::{CanvasConfig, CanvasScale},
+        input::gamepad::GAMEPAD_POINTER,
+        testing::CanvasTestApp,
+    };
+    use bevy::{ecs::system::SystemState, input::touch::TouchPhase};
+
+    use super::*;
+
+    fn app() -> CanvasTestApp {
+        let config = CanvasConfig {
+            resolution: Vec2::new(320.0, 180.0),
+            scale: CanvasScale::Integer,
+            ..default()
+        };
+        let mut app = CanvasTestApp::with_plugins(config, 960.0, 540.0, CursorPlugin);
+        app.update();
+        app
+    }
+
+    /// The visibility and color of each cursor, by pointer.
+    fn cursors(app: &mut CanvasTestApp) -> HashMap<PointerId, (Visibility, Color)> {
+        app.app
+            .world_mut()
+            .query::<(&Cursor, &Visibility, &Sprite)>()
+            .iter(app.app.world())
+            .map(|(cursor, visibility, sprite)| (cursor.pointer_id, (*visibility, sprite.color)))
+            .collect()
+    }
+
+    #[test]
+    fn only_the_mouse_cursor_shows_at_first() {
+        let mut app = app();
+
+        let cursors = cursors(&mut app);
+        assert_eq!(cursors.len(), 2);
+        assert_eq!(cursors[&PointerId::Mouse].0, Visibility::Inherited);
+        assert_eq!(cursors[&GAMEPAD_POINTER].0, Visibility::Hidden);
+    }
+
+    #[test]
+    fn gamepad_cursor_shows_once_it_moves() {
+        let mut app = app();
+        let gamepad = app.connect_gamepad(0);
+
+        app.move_stick(gamepad, Vec2::X);
+        app.update();
+        assert_eq!(cursors(&mut app)[&GAMEPAD_POINTER].0, Visibility::Inherited);
+    }
+
+    #[test]
+    fn touch_cursors_follow_their_pointers_hidden() {
+        let mut app = app();
+
+        app.touch(3, TouchPhase::Started, Vec2::new(480.0, 270.0));
+        app.update();
+        let cursors_while_touching = cursors(&mut app);
+        assert_eq!(cursors_while_touching.len(), 3);
+        assert_eq!(
+            cursors_while_touching[&PointerId::Touch(3)].0,
+            Visibility::Hidden
+        );
+
+        app.touch(3, TouchPhase::Ended, Vec2::new(480.0, 270.0));
+        app.update();
+        app.update();
+        let cursors = cursors(&mut app);
+        assert_eq!(cursors.len(), 2);
+        assert!(!cursors.contains_key(&PointerId::Touch(3)));
+    }
+
+    #[test]
+    fn tints_color_the_cursor_of_their_pointer() {
+        let mut app = app();
+        let mouse = app
+            .app
+            .world_mut()
+            .query::<(Entity, &PointerId)>()
+            .iter(app.app.world())
+            .find_map(|(entity, id)| id.is_mouse().then_some(entity))
+            .unwrap();
+
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        app.app
+            .world_mut()
+            .entity_mut(mouse)
+            .insert(CursorTint(red));
+        app.update();
+        let cursors = cursors(&mut app);
+        assert_eq!(cursors[&PointerId::Mouse].1, red);
+        assert_eq!(cursors[&GAMEPAD_POINTER].1, Color::WHITE);
+    }
+
+    /// Spawns `hints` as a chain of entities from the root down, returning
+    /// the innermost one.
+    fn hinted(world: &mut World, hints: &[(Option<&'static str>, i32)]) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = Vec::new();
+        for &(hint, priority) in hints {
+            let mut entity = world.spawn(CursorHintPriority(priority));
+            if let Some(hint) = hint {
+                entity.insert(CursorHint(CursorState::from_static(hint)));
+            }
+            let entity = entity.id();
+            if let Some(&parent) = entities.last() {
+                world.entity_mut(parent).add_child(entity);
+            }
+            entities.push(entity);
+        }
+        entities
+    }
+
+    /// The entity and state [`find_hint`] resolves from the innermost entity.
+    fn resolve(hints: &[(Option<&'static str>, i32)]) -> Option<(usize, String)> {
+        let mut world = World::new();
+        let entities = hinted(&mut world, hints);
+        let mut state = SystemState::<HintQuery>::new(&mut world);
+        let query = state.get(&world);
+        find_hint(*entities.last()?, &query).map(|(entity, hint)| {
+            let index = entities.iter().position(|&other| other == entity).unwrap();
+            (index, hint.0.name().to_owned())
+        })
+    }
+
+    #[test]
+    fn unhinted_entities_have_no_hint() {
+        assert_eq!(resolve(&[(None, 0), (None, 0)]), None);
+    }
+
+    #[test]
+    fn innermost_hint_wins() {
+        assert_eq!(
+            resolve(&[(Some("Outer"), 0), (Some("Inner"), 0), (None, 0)]),
+            Some((1, "Inner".into()))
+        );
+    }
+
+    #[test]
+    fn higher_priority_ancestors_win() {
+        assert_eq!(
+            resolve(&[(Some("Outer"), 1), (Some("Inner"), 0)]),
+            Some((0, "Outer".into()))
+        );
+        assert_eq!(
+            resolve(&[(Some("Outer"), 2), (Some("Middle"), 3), (Some("Inner"), 1)]),
+            Some((1, "Middle".into()))
+        );
+    }
+
+    #[test]
+    fn lower_priority_ancestors_lose() {
+        assert_eq!(
+            resolve(&[(Some("Outer"), -1), (Some("Inner"), 0)]),
+            Some((1, "Inner".into()))
+        );
+        // Priorities without a hint don't count.
+        assert_eq!(
+            resolve(&[(None, 5), (Some("Inner"), 0)]),
+            Some((1, "Inner".into()))
+        );
+    }
+
+    #[test]
+    fn draggables_are_hinted_and_snapped_to() {
+        let mut app = app();
+        let plain = app.app.world_mut().spawn(Draggable).id();
+        let hinted = app
+            .app
+            .world_mut()
+            .spawn((Draggable, CursorHint(CursorState::CROSSHAIR)))
+            .id();
+        app.update();
+
+        let world = app.app.world();
+        assert!(world.entity(plain).contains::<PointerSnap>());
+        assert!(world.entity(hinted).contains::<PointerSnap>());
+        assert_eq!(
+            world.get::<CursorHint>(plain),
+            Some(&CursorHint(CursorState::GRAB))
+        );
+        assert_eq!(
+            world.get::<CursorHint>(hinted),
+            Some(&CursorHint(CursorState::CROSSHAIR))
+        );
     }
-    let cursor = state.get_data(config.as_ref());
-    *handle = cursor.texture.clone();
-    sprite.anchor = cursor.anchor;
 }
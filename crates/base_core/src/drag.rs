@@ -0,0 +1,580 @@
+use std::borrow::Cow;
+
+use base_retro::canvas::CanvasCamera;
+use bevy::prelude::*;
+use bevy_defer::{
+    cancellation::{SyncCancellation, TaskCancellation},
+    tween::Playback,
+    AsyncAccess, AsyncCommandsExtension, AsyncWorld,
+};
+use bevy_mod_picking::{focus::HoverMap, pointer::PointerLocation, prelude::*};
+use interpolation::Ease;
+
+use crate::cursor::{find_draggable, DraggableQuery};
+
+pub struct DragPlugin;
+
+impl Plugin for DragPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DragStarted>()
+            .add_event::<DroppedOn>()
+            .add_event::<DragCancelled>()
+            .add_systems(
+                Update,
+                (start_drags, follow_pointers, end_drags, cancel_lost_drags).chain(),
+            );
+    }
+}
+
+/// What a [`Draggable`](crate::cursor::Draggable) carries, checked by the
+/// [`DropZone`]s it is dropped on.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DragPayload(pub Cow<'static, str>);
+
+impl DragPayload {
+    pub fn new(kind: impl Into<Cow<'static, str>>) -> Self {
+        Self(kind.into())
+    }
+}
+
+/// Accepts dropped [`Draggable`](crate::cursor::Draggable)s carrying one of
+/// the `accepts` payloads, or any payload when empty. Rejected drops move
+/// back to where they started.
+#[derive(Component, Clone, Debug, Default)]
+pub struct DropZone {
+    pub accepts: Vec<DragPayload>,
+}
+
+impl DropZone {
+    pub fn accepting(payloads: impl IntoIterator<Item = DragPayload>) -> Self {
+        Self {
+            accepts: payloads.into_iter().collect(),
+        }
+    }
+
+    pub fn accepts(&self, payload: Option<&DragPayload>) -> bool {
+        self.accepts.is_empty() || payload.is_some_and(|payload| self.accepts.contains(payload))
+    }
+}
+
+/// Snaps a dragged [`Draggable`](crate::cursor::Draggable) to multiples of
+/// this size, in canvas pixels.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SnapToGrid(pub Vec2);
+
+impl SnapToGrid {
+    /// The grid point nearest to `position`.
+    pub fn snap(&self, position: Vec2) -> Vec2 {
+        (position / self.0).round() * self.0
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DragStarted {
+    pub entity: Entity,
+    pub pointer_id: PointerId,
+}
+
+/// A drag ended where it was dropped, `zone` is `None` outside any
+/// [`DropZone`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DroppedOn {
+    pub entity: Entity,
+    pub zone: Option<Entity>,
+}
+
+/// A drag was dropped on a [`DropZone`] rejecting it, or lost its pointer
+/// with `zone` being `None`, and moves back.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DragCancelled {
+    pub entity: Entity,
+    pub zone: Option<Entity>,
+}
+
+/// A [`Draggable`](crate::cursor::Draggable) held by a pointer.
+#[derive(Component)]
+struct Dragging {
+    pointer_id: PointerId,
+    /// Translation when the drag started.
+    origin: Vec3,
+    /// World position of the pointer when the drag started.
+    start: Vec2,
+    /// Restored when dropped, the dragged entity doesn't block the drop zones
+    /// under it meanwhile.
+    pickable: Option<Pickable>,
+}
+
+/// Cancels the move back to the origin when dragged again.
+#[derive(Component)]
+struct Returning(SyncCancellation);
+
+fn pointer_world_position(
+    pointer_id: PointerId,
+    pointer_map: &PointerMap,
+    pointers: &Query<&PointerLocation>,
+    camera: (&Camera, &GlobalTransform),
+) -> Option<Vec2> {
+    let location = pointers
+        .get(pointer_map.get_entity(pointer_id)?)
+        .ok()?
+        .location()?;
+    camera.0.viewport_to_world_2d(camera.1, location.position)
+}
+
+/// Hovered entities under `pointer_id`, nearest first.
+fn hovered_by_depth(hover_map: &HoverMap, pointer_id: PointerId) -> Vec<Entity> {
+    let mut hovered: Vec<_> = hover_map.get(&pointer_id).into_iter().flatten().collect();
+    hovered.sort_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+    hovered.into_iter().map(|(&entity, _)| entity).collect()
+}
+
+fn start_drags(
+    mut commands: Commands,
+    mut drag_starts: EventReader<Pointer<DragStart>>,
+    camera: Query<(&Camera, &GlobalTransform), With<CanvasCamera>>,
+    draggables: DraggableQuery,
+    dragged: Query<(&Transform, Option<&Pickable>, Option<&Returning>)>,
+    dragging: Query<&Dragging>,
+    mut started: EventWriter<DragStarted>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+
+    // Every entity under the pointer when it was pressed gets its own drag
+    // start, the nearest draggable among them is dragged.
+    let mut drag_starts: Vec<_> = drag_starts
+        .read()
+        .filter(|event| event.button == PointerButton::Primary)
+        .collect();
+    drag_starts.sort_by(|a, b| a.hit.depth.total_cmp(&b.hit.depth));
+
+    let mut handled = Vec::new();
+    for event in drag_starts {
+        if handled.contains(&event.pointer_id)
+            || dragging
+                .iter()
+                .any(|dragging| dragging.pointer_id == event.pointer_id)
+        {
+            continue;
+        }
+        let Some(entity) = find_draggable(event.target, &draggables) else {
+            continue;
+        };
+        let Ok((transform, pickable, returning)) = dragged.get(entity) else {
+            continue;
+        };
+        // Where the pointer was pressed, it moved on since.
+        let Some(start) = camera
+            .0
+            .viewport_to_world_2d(camera.1, event.pointer_location.position)
+        else {
+            continue;
+        };
+
+        handled.push(event.pointer_id);
+        if let Some(Returning(cancel)) = returning {
+            cancel.cancel();
+        }
+        commands.entity(entity).remove::<Returning>().insert((
+            Dragging {
+                pointer_id: event.pointer_id,
+                origin: transform.translation,
+                start,
+                pickable: pickable.cloned(),
+            },
+            Pickable {
+                should_block_lower: false,
+                is_hoverable: true,
+            },
+        ));
+        started.send(DragStarted {
+            entity,
+            pointer_id: event.pointer_id,
+        });
+    }
+}
+
+fn follow_pointers(
+    pointer_map: Res<PointerMap>,
+    pointers: Query<&PointerLocation>,
+    camera: Query<(&Camera, &GlobalTransform), With<CanvasCamera>>,
+    mut dragged: Query<(&Dragging, &mut Transform, Option<&SnapToGrid>)>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+
+    for (dragging, mut transform, snap) in &mut dragged {
+        let Some(position) =
+            pointer_world_position(dragging.pointer_id, &pointer_map, &pointers, camera)
+        else {
+            continue;
+        };
+        let mut target = dragging.origin.truncate() + position - dragging.start;
+        if let Some(snap) = snap {
+            target = snap.snap(target);
+        }
+        transform.translation = target.extend(dragging.origin.z);
+    }
+}
+
+fn end_drags(
+    mut commands: Commands,
+    mut drag_ends: EventReader<Pointer<DragEnd>>,
+    hover_map: Res<HoverMap>,
+    dragged: Query<(Entity, &Dragging, &Transform, Option<&DragPayload>)>,
+    zones: Query<(Option<&DropZone>, Option<&Parent>)>,
+    mut dropped: EventWriter<DroppedOn>,
+    mut cancelled: EventWriter<DragCancelled>,
+) {
+    let mut handled = Vec::new();
+    for event in drag_ends.read() {
+        let Some((entity, dragging, transform, payload)) = dragged
+            .iter()
+            .find(|(_, dragging, ..)| dragging.pointer_id == event.pointer_id)
+            .filter(|_| !handled.contains(&event.pointer_id))
+        else {
+            continue;
+        };
+        handled.push(event.pointer_id);
+
+        release(&mut commands, entity, dragging);
+
+        // The nearest zone under the pointer, skipping the dragged entity.
+        let zone = hovered_by_depth(&hover_map, event.pointer_id)
+            .into_iter()
+            .find_map(|mut hit| {
+                while let Ok((zone, parent)) = zones.get(hit) {
+                    if hit == entity {
+                        return None;
+                    }
+                    if let Some(zone) = zone {
+                        return Some((hit, zone));
+                    }
+                    hit = parent?.get();
+                }
+                None
+            });
+
+        match zone {
+            Some((zone, drop_zone)) if !drop_zone.accepts(payload) => {
+                move_back(
+                    &mut commands,
+                    entity,
+                    transform.translation,
+                    dragging.origin,
+                );
+                cancelled.send(DragCancelled {
+                    entity,
+                    zone: Some(zone),
+                });
+            }
+            zone => {
+                dropped.send(DroppedOn {
+                    entity,
+                    zone: zone.map(|(zone, _)| zone),
+                });
+            }
+        }
+    }
+}
+
+/// Cancels the drags of pointers that were despawned, like a disconnected
+/// gamepad or a touch that never ended.
+fn cancel_lost_drags(
+    mut commands: Commands,
+    pointer_map: Res<PointerMap>,
+    dragged: Query<(Entity, &Dragging, &Transform)>,
+    mut cancelled: EventWriter<DragCancelled>,
+) {
+    for (entity, dragging, transform) in &dragged {
+        if pointer_map.get_entity(dragging.pointer_id).is_some() {
+            continue;
+        }
+        release(&mut commands, entity, dragging);
+        move_back(
+            &mut commands,
+            entity,
+            transform.translation,
+            dragging.origin,
+        );
+        cancelled.send(DragCancelled { entity, zone: None });
+    }
+}
+
+/// Ends the drag of `entity`, restoring its [`Pickable`].
+fn release(commands: &mut Commands, entity: Entity, dragging: &Dragging) {
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.remove::<(Dragging, Pickable)>();
+    if let Some(pickable) = dragging.pickable.clone() {
+        entity_commands.insert(pickable);
+    }
+}
+
+/// Moves `entity` back from `from` to `to`, marked [`Returning`] until it
+/// arrives.
+fn move_back(commands: &mut Commands, entity: Entity, from: Vec3, to: Vec3) {
+    let cancel = SyncCancellation::default();
+    commands.entity(entity).insert(Returning(cancel.clone()));
+    commands.spawn_task(move || async move {
+        let transform = AsyncWorld.entity(entity).component::<Transform>();
+        transform
+            .interpolate(
+                move |x| from.lerp(to, x),
+                |t, v| t.translation = v,
+                f32::quadratic_out,
+                0.25,
+                Playback::Once,
+                &cancel,
+            )
+            .await?;
+        // A new drag already removed the marker, and may have added another.
+        if TaskCancellation::from(&cancel).cancelled() {
+            return Ok(());
+        }
+        AsyncWorld.entity(entity).remove::<Returning>()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use base_retro::{
+        canvas::{CanvasConfig, CanvasScale},
+        testing::CanvasTestApp,
+    };
+    use bevy::input::{mouse::MouseButtonInput, ButtonState};
+    use bevy_defer::AsyncPlugin;
+    use bevy_mod_picking::{
+        backends::sprite::SpriteBackend, picking_core::InteractionPlugin, pointer::PointerId,
+    };
+
+    use super::*;
+    use crate::cursor::Draggable;
+
+    fn app() -> CanvasTestApp {
+        let config = CanvasConfig {
+            resolution: Vec2::new(320.0, 180.0),
+            scale: CanvasScale::Integer,
+            ..default()
+        };
+        CanvasTestApp::with_plugins(
+            config,
+            960.0,
+            540.0,
+            (
+                InteractionPlugin,
+                SpriteBackend,
+                AsyncPlugin::default_settings(),
+                DragPlugin,
+                |app: &mut App| {
+                    app.init_resource::<Received<DragStarted>>()
+                        .init_resource::<Received<DroppedOn>>()
+                        .init_resource::<Received<DragCancelled>>()
+                        .add_systems(
+                            Last,
+                            (
+                                receive::<DragStarted>,
+                                receive::<DroppedOn>,
+                                receive::<DragCancelled>,
+                            ),
+                        );
+                },
+            ),
+        )
+    }
+
+    /// Events sent since they were last taken, kept longer than the two
+    /// frames events last.
+    #[derive(Resource)]
+    struct Received<E>(Vec<E>);
+
+    impl<E> Default for Received<E> {
+        fn default() -> Self {
+            Self(Vec::new())
+        }
+    }
+
+    fn receive<E: Event + Clone>(mut events: EventReader<E>, mut received: ResMut<Received<E>>) {
+        received.0.extend(events.read().cloned());
+    }
+
+    /// Spawns a square sprite `size` canvas pixels wide at `translation`.
+    fn spawn_square(app: &mut CanvasTestApp, translation: Vec3, size: f32) -> EntityWorldMut<'_> {
+        app.app.world_mut().spawn(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_translation(translation),
+            ..default()
+        })
+    }
+
+    /// Moves the mouse over `position` in world units, three window pixels
+    /// each.
+    fn move_to(app: &mut CanvasTestApp, position: Vec2) {
+        app.move_cursor(Vec2::new(
+            480.0 + position.x * 3.0,
+            270.0 - position.y * 3.0,
+        ));
+    }
+
+    fn press(app: &mut CanvasTestApp, state: ButtonState) {
+        let window = app.window();
+        app.app.world_mut().send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window,
+        });
+        app.update();
+    }
+
+    /// Drags with the mouse from `from` to `to` in world units, without
+    /// letting go.
+    fn drag(app: &mut CanvasTestApp, from: Vec2, to: Vec2) {
+        move_to(app, from);
+        press(app, ButtonState::Pressed);
+        move_to(app, from.lerp(to, 0.5));
+        move_to(app, to);
+    }
+
+    /// The translation of `entity` to a thousandth of a pixel, mapping the
+    /// pointer through the camera isn't exact.
+    fn translation(app: &CanvasTestApp, entity: Entity) -> Vec2 {
+        let translation = app
+            .app
+            .world()
+            .get::<Transform>(entity)
+            .unwrap()
+            .translation;
+        (translation.truncate() * 1000.0).round() / 1000.0
+    }
+
+    fn events<E: Event + Clone>(app: &mut CanvasTestApp) -> Vec<E> {
+        std::mem::take(&mut app.app.world_mut().resource_mut::<Received<E>>().0)
+    }
+
+    /// Runs frames until the move back to the origin is over.
+    fn finish_returning(app: &mut CanvasTestApp, entity: Entity) {
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!(!app.app.world().entity(entity).contains::<Returning>());
+    }
+
+    #[test]
+    fn draggables_follow_the_pointer_onto_drop_zones() {
+        let mut app = app();
+        let card = spawn_square(&mut app, Vec3::new(0.0, 0.0, 1.0), 16.0)
+            .insert((Draggable, DragPayload::new("card")))
+            .id();
+        let zone = spawn_square(&mut app, Vec3::new(60.0, 20.0, 0.0), 32.0)
+            .insert(DropZone::accepting([DragPayload::new("card")]))
+            .id();
+        app.update();
+
+        // Grabbing off center keeps the offset to the pointer.
+        drag(&mut app, Vec2::new(2.0, 0.0), Vec2::new(32.0, 10.0));
+        let started = events::<DragStarted>(&mut app);
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].entity, card);
+        assert_eq!(started[0].pointer_id, PointerId::Mouse);
+        assert_eq!(translation(&app, card), Vec2::new(30.0, 10.0));
+
+        move_to(&mut app, Vec2::new(62.0, 20.0));
+        assert_eq!(translation(&app, card), Vec2::new(60.0, 20.0));
+        press(&mut app, ButtonState::Released);
+
+        let dropped = events::<DroppedOn>(&mut app);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].entity, card);
+        assert_eq!(dropped[0].zone, Some(zone));
+        assert!(events::<DragCancelled>(&mut app).is_empty());
+        assert!(!app.app.world().entity(card).contains::<Dragging>());
+        assert!(!app.app.world().entity(card).contains::<Pickable>());
+        assert_eq!(translation(&app, card), Vec2::new(60.0, 20.0));
+    }
+
+    #[test]
+    fn rejected_drops_move_back() {
+        let mut app = app();
+        let coin = spawn_square(&mut app, Vec3::new(0.0, 0.0, 1.0), 16.0)
+            .insert((Draggable, DragPayload::new("coin")))
+            .id();
+        let zone = spawn_square(&mut app, Vec3::new(-50.0, 0.0, 0.0), 32.0)
+            .insert(DropZone::accepting([DragPayload::new("card")]))
+            .id();
+        app.update();
+
+        drag(&mut app, Vec2::ZERO, Vec2::new(-50.0, 0.0));
+        press(&mut app, ButtonState::Released);
+
+        let cancelled = events::<DragCancelled>(&mut app);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].entity, coin);
+        assert_eq!(cancelled[0].zone, Some(zone));
+        assert!(events::<DroppedOn>(&mut app).is_empty());
+        assert!(app.app.world().entity(coin).contains::<Returning>());
+
+        finish_returning(&mut app, coin);
+        assert_eq!(translation(&app, coin), Vec2::ZERO);
+    }
+
+    #[test]
+    fn drags_of_lost_pointers_are_cancelled() {
+        let mut app = app();
+        let card = spawn_square(&mut app, Vec3::new(10.0, 10.0, 1.0), 16.0)
+            .insert(Draggable)
+            .id();
+        app.update();
+
+        drag(&mut app, Vec2::new(10.0, 10.0), Vec2::new(40.0, -20.0));
+        assert_eq!(translation(&app, card), Vec2::new(40.0, -20.0));
+
+        let pointer = app
+            .app
+            .world_mut()
+            .query::<(Entity, &PointerId)>()
+            .iter(app.app.world())
+            .find_map(|(entity, &id)| (id == PointerId::Mouse).then_some(entity))
+            .unwrap();
+        app.app.world_mut().despawn(pointer);
+        app.update();
+
+        let cancelled = events::<DragCancelled>(&mut app);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].entity, card);
+        assert_eq!(cancelled[0].zone, None);
+        assert!(!app.app.world().entity(card).contains::<Dragging>());
+
+        finish_returning(&mut app, card);
+        assert_eq!(translation(&app, card), Vec2::new(10.0, 10.0));
+    }
+
+    #[test]
+    fn empty_drop_zones_accept_anything() {
+        let zone = DropZone::default();
+        assert!(zone.accepts(None));
+        assert!(zone.accepts(Some(&DragPayload::new("card"))));
+    }
+
+    #[test]
+    fn drop_zones_accept_their_payloads() {
+        let zone = DropZone::accepting([DragPayload::new("card"), DragPayload::new("coin")]);
+        assert!(zone.accepts(Some(&DragPayload::new("card"))));
+        assert!(zone.accepts(Some(&DragPayload::new("coin"))));
+        assert!(!zone.accepts(Some(&DragPayload::new("gem"))));
+        assert!(!zone.accepts(None));
+    }
+
+    #[test]
+    fn snapping_rounds_to_the_nearest_grid_point() {
+        let snap = SnapToGrid(Vec2::new(16.0, 8.0));
+        assert_eq!(snap.snap(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(snap.snap(Vec2::new(7.9, 3.9)), Vec2::ZERO);
+        assert_eq!(snap.snap(Vec2::new(8.1, 4.1)), Vec2::new(16.0, 8.0));
+        assert_eq!(snap.snap(Vec2::new(-25.0, -13.0)), Vec2::new(-32.0, -16.0));
+        assert_eq!(snap.snap(Vec2::new(40.0, 21.0)), Vec2::new(48.0, 24.0));
+    }
+}
@@ -0,0 +1,300 @@
+use base_retro::canvas::{CanvasCamera, CanvasHandle};
+use bevy::{prelude::*, render::camera::NormalizedRenderTarget};
+use bevy_mod_picking::{
+    backend::HitData,
+    pointer::{Location, Uuid},
+    prelude::*,
+};
+use leafwing_input_manager::prelude::*;
+
+use crate::context::{InputContext, InputContextApp};
+
+/// Pointer the [`Pointer<Click>`] events sent when activating the focused
+/// node come from.
+pub const FOCUS_POINTER: PointerId =
+    PointerId::Custom(Uuid::from_u128(0x1f6a_93c2_7d48_4b05_a2e1_5c90_d37b_084e));
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(InputManagerPlugin::<FocusAction>::default())
+            .add_input_context::<FocusAction>(InputContext::Menu)
+            .init_resource::<Focus>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (clear_focus, navigate, activate, draw_focus_ring)
+                    .chain()
+                    .in_set(FocusSystems),
+            );
+    }
+}
+
+/// Systems moving, activating and drawing the [`Focus`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FocusSystems;
+
+/// A UI node that can be focused with the keyboard or a gamepad.
+#[derive(Component, Default)]
+pub struct Focusable;
+
+/// The focused [`Focusable`] node.
+#[derive(Resource, Default, Deref)]
+pub struct Focus(Option<Entity>);
+
+impl Focus {
+    pub fn set(&mut self, entity: Option<Entity>) {
+        self.0 = entity;
+    }
+}
+
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+enum FocusAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Activate,
+}
+
+impl FocusAction {
+    fn direction(&self) -> Option<Vec2> {
+        // UI positions grow downwards.
+        match self {
+            FocusAction::Up => Some(Vec2::NEG_Y),
+            FocusAction::Down => Some(Vec2::Y),
+            FocusAction::Left => Some(Vec2::NEG_X),
+            FocusAction::Right => Some(Vec2::X),
+            FocusAction::Activate => None,
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let mut input_map = InputMap::new([
+        (FocusAction::Up, KeyCode::ArrowUp),
+        (FocusAction::Up, KeyCode::KeyW),
+        (FocusAction::Down, KeyCode::ArrowDown),
+        (FocusAction::Down, KeyCode::KeyS),
+        (FocusAction::Left, KeyCode::ArrowLeft),
+        (FocusAction::Left, KeyCode::KeyA),
+        (FocusAction::Right, KeyCode::ArrowRight),
+        (FocusAction::Right, KeyCode::KeyD),
+        (FocusAction::Activate, KeyCode::Enter),
+        (FocusAction::Activate, KeyCode::Space),
+    ]);
+    input_map
+        .insert(FocusAction::Up, GamepadButtonType::DPadUp)
+        .insert(FocusAction::Down, GamepadButtonType::DPadDown)
+        .insert(FocusAction::Left, GamepadButtonType::DPadLeft)
+        .insert(FocusAction::Right, GamepadButtonType::DPadRight)
+        .insert(FocusAction::Activate, GamepadButtonType::South);
+
+    commands.spawn(InputManagerBundle::with_map(input_map));
+}
+
+fn clear_focus(mut focus: ResMut<Focus>, focusables: Query<(), With<Focusable>>) {
+    if focus.is_some_and(|entity| !focusables.contains(entity)) {
+        focus.set(None);
+    }
+}
+
+fn navigate(
+    mut focus: ResMut<Focus>,
+    actions: Query<&ActionState<FocusAction>>,
+    focusables: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Focusable>>,
+) {
+    let Some(direction) = actions
+        .iter()
+        .flat_map(|action| action.get_just_pressed())
+        .find_map(|action| action.direction())
+    else {
+        return;
+    };
+
+    let visible = focusables
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, transform, _)| (entity, transform.translation().xy()));
+
+    let Some(from) = focus.and_then(|entity| focusables.get(entity).ok()) else {
+        // Start from the top left node.
+        let first = visible.min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+        focus.set(first.map(|(entity, _)| entity));
+        return;
+    };
+    let from = from.1.translation().xy();
+
+    // Prefer nodes straight ahead over closer ones off to the side.
+    let nearest = visible
+        .filter_map(|(entity, position)| {
+            let offset = position - from;
+            let along = offset.dot(direction);
+            let across = offset.perp_dot(direction).abs();
+            (along > 0.0).then_some((entity, along + across * 2.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((entity, _)) = nearest {
+        focus.set(Some(entity));
+    }
+}
+
+fn activate(
+    focus: Res<Focus>,
+    canvas: Res<CanvasHandle>,
+    actions: Query<&ActionState<FocusAction>>,
+    focusables: Query<&GlobalTransform, With<Focusable>>,
+    camera: Query<Entity, With<CanvasCamera>>,
+    mut clicks: EventWriter<Pointer<Click>>,
+) {
+    if !actions
+        .iter()
+        .any(|action| action.just_pressed(&FocusAction::Activate))
+    {
+        return;
+    }
+    let Some((entity, transform)) =
+        focus.and_then(|entity| Some((entity, focusables.get(entity).ok()?)))
+    else {
+        return;
+    };
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+
+    clicks.send(Pointer::new(
+        FOCUS_POINTER,
+        Location {
+            target: NormalizedRenderTarget::Image(canvas.clone()),
+            position: transform.translation().xy(),
+        },
+        entity,
+        Click {
+            button: PointerButton::Primary,
+            hit: HitData::new(camera, 0.0, Some(transform.translation()), None),
+        },
+    ));
+}
+
+/// Outlines the focused node, one canvas pixel wide.
+fn draw_focus_ring(mut commands: Commands, focus: Res<Focus>, mut last: Local<Option<Entity>>) {
+    if !focus.is_changed() || *last == **focus {
+        return;
+    }
+
+    if let Some(mut entity) = last.and_then(|entity| commands.get_entity(entity)) {
+        entity.remove::<Outline>();
+    }
+    if let Some(mut entity) = focus.and_then(|entity| commands.get_entity(entity)) {
+        entity.insert(Outline::new(Val::Px(1.0), Val::Px(1.0), Color::WHITE));
+    }
+    *last = **focus;
+}
+
+#[cfg(test)]
+mod tests {
+    use base_retro::{canvas::CanvasConfig, testing::CanvasTestApp};
+
+    use super::*;
+    use crate::context::InputContexts;
+
+    fn app() -> CanvasTestApp {
+        let mut app = CanvasTestApp::with_plugins(
+            CanvasConfig::default(),
+            960.0,
+            540.0,
+            (
+                |app: &mut App| {
+                    app.add_event::<Pointer<Click>>();
+                },
+                FocusPlugin,
+            ),
+        );
+        app.app
+            .world_mut()
+            .resource_mut::<InputContexts>()
+            .push(InputContext::Menu);
+        app
+    }
+
+    fn spawn(app: &mut CanvasTestApp, x: f32, y: f32, visibility: Visibility) -> Entity {
+        app.app
+            .world_mut()
+            .spawn((
+                Focusable,
+                SpatialBundle {
+                    transform: Transform::from_xyz(x, y, 0.0),
+                    visibility,
+                    ..default()
+                },
+            ))
+            .id()
+    }
+
+    /// Presses and releases `key`, running a frame after each.
+    fn tap(app: &mut CanvasTestApp, key: KeyCode) {
+        for pressed in [true, false] {
+            let mut keys = app.app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            if pressed {
+                keys.press(key);
+            } else {
+                keys.release(key);
+            }
+            app.update();
+        }
+    }
+
+    fn focus(app: &CanvasTestApp) -> Option<Entity> {
+        **app.app.world().resource::<Focus>()
+    }
+
+    #[test]
+    fn directions_move_to_the_nearest_node_ahead() {
+        let mut app = app();
+        let top_left = spawn(&mut app, 0.0, 0.0, Visibility::Inherited);
+        let top_right = spawn(&mut app, 100.0, 0.0, Visibility::Inherited);
+        let bottom_left = spawn(&mut app, 0.0, 50.0, Visibility::Inherited);
+        let bottom_right = spawn(&mut app, 100.0, 60.0, Visibility::Inherited);
+        spawn(&mut app, 100.0, 30.0, Visibility::Hidden);
+        app.update();
+
+        // The first press focuses the top left node.
+        tap(&mut app, KeyCode::ArrowDown);
+        assert_eq!(focus(&app), Some(top_left));
+
+        tap(&mut app, KeyCode::ArrowRight);
+        assert_eq!(focus(&app), Some(top_right));
+        // Straight down beats the closer node off to the side, hidden nodes
+        // are skipped.
+        tap(&mut app, KeyCode::KeyS);
+        assert_eq!(focus(&app), Some(bottom_right));
+        tap(&mut app, KeyCode::ArrowLeft);
+        assert_eq!(focus(&app), Some(bottom_left));
+
+        // Nothing further left keeps the focus.
+        tap(&mut app, KeyCode::ArrowLeft);
+        assert_eq!(focus(&app), Some(bottom_left));
+    }
+
+    #[test]
+    fn activating_clicks_the_focused_node() {
+        let mut app = app();
+        let node = spawn(&mut app, 20.0, 10.0, Visibility::Inherited);
+        app.update();
+
+        // Nothing is clicked without a focus.
+        tap(&mut app, KeyCode::Enter);
+        tap(&mut app, KeyCode::ArrowDown);
+        tap(&mut app, KeyCode::Enter);
+
+        let events = app.app.world().resource::<Events<Pointer<Click>>>();
+        let clicks: Vec<_> = events.get_reader().read(events).cloned().collect();
+        assert_eq!(clicks.len(), 1);
+        assert_eq!(clicks[0].target, node);
+        assert_eq!(clicks[0].pointer_id, FOCUS_POINTER);
+        assert_eq!(clicks[0].pointer_location.position, Vec2::new(20.0, 10.0));
+        assert_eq!(clicks[0].event.button, PointerButton::Primary);
+    }
+}
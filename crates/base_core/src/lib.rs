@@ -1,11 +1,31 @@
+pub mod bindings;
+pub mod camera;
+pub mod capture;
+pub mod context;
 pub mod cursor;
+pub mod drag;
 pub mod enemy;
+pub mod focus;
 pub mod menu;
 pub mod player;
 pub mod quick_close;
+pub mod replay;
+pub mod touch_controls;
 
 pub mod prelude {
     pub use crate::{
-        enemy::EnemyPlugin, menu::MenuPlugin, player::PlayerPlugin, quick_close::QuickClosePlugin,
+        bindings::BindingsPlugin,
+        camera::CameraPlugin,
+        capture::CapturePlugin,
+        context::{InputContext, InputContextApp, InputContexts},
+        cursor::CursorPlugin,
+        drag::DragPlugin,
+        enemy::EnemyPlugin,
+        focus::FocusPlugin,
+        menu::{GameState, MenuPlugin},
+        player::PlayerPlugin,
+        quick_close::QuickClosePlugin,
+        replay::{ReplayMode, ReplayPlugin},
+        touch_controls::TouchControlsPlugin,
     };
 }
@@ -1,17 +1,48 @@
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 
+use crate::{
+    bindings::open_controls,
+    context::{InputContext, InputContextApp},
+    focus::Focusable,
+};
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup);
+        app.init_state::<GameState>()
+            .add_state_input_context(GameState::Menu, InputContext::Menu)
+            .add_systems(Startup, setup)
+            .add_systems(OnExit(GameState::Menu), hide_menu);
     }
 }
 
+/// Whether the game waits in the main menu or is being played.
+#[derive(States, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Menu,
+    Playing,
+}
+
+#[derive(Component)]
+pub struct MainMenu;
+
+fn start_game(mut state: ResMut<NextState<GameState>>) {
+    state.set(GameState::Playing);
+}
+
+fn hide_menu(mut menus: Query<&mut Visibility, With<MainMenu>>) {
+    menus
+        .iter_mut()
+        .for_each(|mut visibility| *visibility = Visibility::Hidden);
+}
+
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
         .spawn((
+            MainMenu,
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
@@ -42,7 +73,8 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         background_color: BackgroundColor(Color::BLACK),
                         ..default()
                     },
-                    On::<Pointer<Click>>::run(|| info!("clicked!")),
+                    Focusable,
+                    On::<Pointer<Click>>::run(start_game),
                     On::<Pointer<Over>>::run(|| info!("hovered!")),
                 ))
                 .with_children(|parent| {
@@ -74,6 +106,40 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         background_color: BackgroundColor(Color::BLACK),
                         ..default()
                     },
+                    Focusable,
+                    On::<Pointer<Click>>::run(open_controls),
+                    On::<Pointer<Over>>::run(|| info!("hovered!")),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Controls",
+                            TextStyle {
+                                font: asset_server.load("fonts/x12y12pxMaruMinya.ttf"),
+                                font_size: 12.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        Pickable::IGNORE,
+                    ));
+                });
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(60.0),
+                            height: Val::Px(25.0),
+                            border: UiRect::all(Val::Px(1.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        border_color: BorderColor(Color::WHITE),
+                        background_color: BackgroundColor(Color::BLACK),
+                        ..default()
+                    },
+                    Focusable,
                     On::<Pointer<Click>>::run(|| info!("clicked!")),
                     On::<Pointer<Over>>::run(|| info!("hovered!")),
                 ))
@@ -1,22 +1,47 @@
 use bevy::prelude::*;
 use bevy_vector_shapes::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bindings::PlayerBindings,
+    context::{InputContext, InputContextApp},
+    replay::Checksum,
+};
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<PlayerAction>::default())
+            .add_input_context::<PlayerAction>(InputContext::Gameplay)
+            .insert_resource(PlayerBindings::load())
             .add_systems(Startup, setup)
-            .add_systems(Update, movement);
+            .add_systems(FixedUpdate, movement);
     }
 }
 
 #[derive(Component)]
-struct Player;
+pub struct Player;
 
-#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
-enum PlayerAction {
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub enum PlayerAction {
+    /// Left stick and the [`PlayerBindings`] of the directions, read by
+    /// [`PlayerMovement::direction`].
+    Move,
     Up,
     Down,
     Left,
@@ -24,9 +49,56 @@ enum PlayerAction {
     Shoot,
 }
 
-fn setup(mut commands: Commands, config: Res<BaseShapeConfig>) {
+impl PlayerAction {
+    /// Actions bound to single keys and buttons, which can be rebound.
+    pub const BUTTONS: [PlayerAction; 5] = [
+        PlayerAction::Up,
+        PlayerAction::Down,
+        PlayerAction::Left,
+        PlayerAction::Right,
+        PlayerAction::Shoot,
+    ];
+}
+
+/// How the [`Player`] moves from its [`PlayerAction`]s.
+#[derive(Component, Clone, Debug)]
+pub struct PlayerMovement {
+    /// Speed at full stick tilt or with a direction held, in pixels per
+    /// second.
+    pub speed: f32,
+    /// Stick tilt below which the player stands still.
+    pub deadzone: f32,
+    /// Stick tilt above which the player moves at full speed.
+    pub saturation: f32,
+}
+
+impl Default for PlayerMovement {
+    fn default() -> Self {
+        Self {
+            speed: 180.0,
+            deadzone: 0.15,
+            saturation: 0.9,
+        }
+    }
+}
+
+impl PlayerMovement {
+    /// The direction to move in, at most one long so diagonals aren't faster.
+    pub fn direction(&self, action: &ActionState<PlayerAction>) -> Vec2 {
+        let stick = action
+            .axis_pair(&PlayerAction::Move)
+            .map_or(Vec2::ZERO, |axis| axis.xy());
+        let tilt =
+            ((stick.length() - self.deadzone) / (self.saturation - self.deadzone)).clamp(0.0, 1.0);
+
+        stick.normalize_or_zero() * tilt
+    }
+}
+
+fn setup(mut commands: Commands, config: Res<BaseShapeConfig>, bindings: Res<PlayerBindings>) {
     commands.spawn((
         Player,
+        Checksum,
         ShapeBundle::circle(
             &ShapeConfig {
                 color: Color::WHITE,
@@ -36,34 +108,106 @@ fn setup(mut commands: Commands, config: Res<BaseShapeConfig>) {
             },
             15.0,
         ),
-        InputManagerBundle::with_map(InputMap::new([
-            (PlayerAction::Up, KeyCode::ArrowUp),
-            (PlayerAction::Up, KeyCode::KeyW),
-            (PlayerAction::Down, KeyCode::ArrowDown),
-            (PlayerAction::Down, KeyCode::KeyS),
-            (PlayerAction::Left, KeyCode::ArrowLeft),
-            (PlayerAction::Left, KeyCode::KeyA),
-            (PlayerAction::Right, KeyCode::ArrowRight),
-            (PlayerAction::Right, KeyCode::KeyD),
-            (PlayerAction::Shoot, KeyCode::KeyZ),
-            (PlayerAction::Shoot, KeyCode::KeyJ),
-        ])),
+        PlayerMovement::default(),
+        InputManagerBundle::with_map(bindings.input_map()),
     ));
 }
 
-fn movement(mut player_query: Query<(&mut Transform, &ActionState<PlayerAction>), With<Player>>) {
-    let (mut transform, action) = player_query.single_mut();
+fn movement(
+    time: Res<Time>,
+    mut player_query: Query<
+        (&mut Transform, &PlayerMovement, &ActionState<PlayerAction>),
+        With<Player>,
+    >,
+) {
+    let (mut transform, movement, action) = player_query.single_mut();
+
+    let velocity = movement.direction(action) * movement.speed;
+    transform.translation += (velocity * time.delta_seconds()).extend(0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::InputPlugin;
+    use leafwing_input_manager::axislike::DualAxisData;
 
-    if action.pressed(&PlayerAction::Up) {
-        transform.translation.y += 3.0;
+    use super::*;
+    use crate::bindings::Binding;
+
+    fn direction(stick: Vec2) -> Vec2 {
+        let mut action = ActionState::<PlayerAction>::default();
+        action
+            .action_data_mut_or_default(&PlayerAction::Move)
+            .axis_pair = Some(DualAxisData::from_xy(stick));
+        PlayerMovement::default().direction(&action)
     }
-    if action.pressed(&PlayerAction::Down) {
-        transform.translation.y -= 3.0;
+
+    fn assert_near(direction: Vec2, expected: Vec2) {
+        assert!(
+            direction.distance(expected) < 1e-4,
+            "{direction} != {expected}"
+        );
     }
-    if action.pressed(&PlayerAction::Left) {
-        transform.translation.x -= 3.0;
+
+    #[test]
+    fn diagonals_are_normalized() {
+        // Virtual D-pads hold both axes fully on diagonals.
+        assert_near(
+            direction(Vec2::new(1.0, 1.0)),
+            Vec2::new(1.0, 1.0).normalize(),
+        );
+        assert_near(
+            direction(Vec2::new(-1.0, 1.0)),
+            Vec2::new(-1.0, 1.0).normalize(),
+        );
+        assert_near(direction(Vec2::new(0.0, -1.0)), Vec2::NEG_Y);
+    }
+
+    #[test]
+    fn tilt_maps_from_deadzone_to_saturation() {
+        assert_eq!(direction(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(direction(Vec2::new(0.1, 0.0)), Vec2::ZERO);
+        assert_eq!(direction(Vec2::new(0.0, 0.15)), Vec2::ZERO);
+        assert_near(direction(Vec2::new(0.525, 0.0)), Vec2::new(0.5, 0.0));
+        assert_near(direction(Vec2::new(0.0, -0.9)), Vec2::NEG_Y);
+        assert_near(direction(Vec2::new(0.95, 0.0)), Vec2::X);
     }
-    if action.pressed(&PlayerAction::Right) {
-        transform.translation.x += 3.0;
+
+    /// The direction moved in while `key` is held, going through the
+    /// [`InputMap`] of `bindings`.
+    fn direction_held(bindings: &PlayerBindings, key: KeyCode) -> Vec2 {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            InputPlugin,
+            InputManagerPlugin::<PlayerAction>::default(),
+        ));
+        let player = app
+            .world_mut()
+            .spawn((Player, InputManagerBundle::with_map(bindings.input_map())))
+            .id();
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+        app.update();
+
+        let action = app
+            .world()
+            .get::<ActionState<PlayerAction>>(player)
+            .unwrap();
+        PlayerMovement::default().direction(action)
+    }
+
+    #[test]
+    fn directions_move_through_the_input_map() {
+        let mut bindings = PlayerBindings::default();
+        assert_near(direction_held(&bindings, KeyCode::KeyW), Vec2::Y);
+        assert_near(direction_held(&bindings, KeyCode::ArrowLeft), Vec2::NEG_X);
+
+        bindings.rebind(PlayerAction::Up, Binding::Key(KeyCode::KeyI));
+        assert_near(direction_held(&bindings, KeyCode::KeyI), Vec2::Y);
+        assert_eq!(direction_held(&bindings, KeyCode::KeyW), Vec2::ZERO);
+        assert_near(direction_held(&bindings, KeyCode::KeyS), Vec2::NEG_Y);
     }
 }
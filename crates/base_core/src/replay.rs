@@ -0,0 +1,773 @@
+//! Records player actions and pointer input by fixed timestep tick, and plays
+//! them back into a fresh app to reproduce a session.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use base_retro::canvas::CanvasHandle;
+use bevy::{
+    app::FixedMain, prelude::*, render::camera::NormalizedRenderTarget, time::TimeUpdateStrategy,
+};
+use bevy_mod_picking::picking_core::{
+    pointer::{InputMove, InputPress, Location, PointerButton, PointerId, PressDirection, Uuid},
+    PickSet,
+};
+use leafwing_input_manager::{
+    axislike::DualAxisData, buttonlike::ButtonState, plugin::InputManagerSystem, prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::player::{Player, PlayerAction};
+
+pub struct ReplayPlugin(pub ReplayMode);
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayTick>()
+            .add_systems(FixedLast, count_tick);
+
+        match &self.0 {
+            ReplayMode::Record(path) => {
+                app.insert_resource(Recorder {
+                    path: path.clone(),
+                    replay: default(),
+                })
+                .add_systems(FixedFirst, record_actions)
+                .add_systems(First, record_pointers.after(PickSet::Input))
+                .add_systems(Last, save_on_exit);
+            }
+            ReplayMode::Play(path) | ReplayMode::Verify(path) => {
+                let replay = Replay::load(path)
+                    .unwrap_or_else(|error| panic!("failed to load the replay: {error:?}"));
+                let timestep = replay.timestep;
+
+                app.insert_resource(Time::<Fixed>::from_duration(timestep))
+                    .insert_resource(Playback::new(replay))
+                    .add_systems(
+                        PreUpdate,
+                        detach_live_input.before(InputManagerSystem::Update),
+                    )
+                    .add_systems(FixedFirst, play_actions)
+                    .add_systems(First, play_pointers.in_set(PickSet::PostInput));
+
+                if let ReplayMode::Verify(_) = self.0 {
+                    // One tick per frame, as fast as the app runs.
+                    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+                        .add_systems(Last, verify);
+                } else {
+                    app.add_plugins(InputManagerPlugin::<ReplayAction>::default())
+                        .add_systems(Startup, setup_hud)
+                        .add_systems(Update, (control_playback, update_hud).chain());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ReplayMode {
+    /// Records the session, saving it to the path on exit.
+    Record(PathBuf),
+    /// Plays a replay back with a HUD to pause, step and change its speed.
+    Play(PathBuf),
+    /// Plays a replay back one tick per frame, then exits with an error if
+    /// the final [`Checksum`] differs from the recorded one.
+    Verify(PathBuf),
+}
+
+impl ReplayMode {
+    /// Reads `--record`, `--replay` or `--verify` followed by a path from the
+    /// command line.
+    pub fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mode = match arg.as_str() {
+                "--record" => ReplayMode::Record,
+                "--replay" => ReplayMode::Play,
+                "--verify" => ReplayMode::Verify,
+                _ => continue,
+            };
+            return args.next().map(|path| mode(path.into()));
+        }
+        None
+    }
+}
+
+/// Marks entities whose [`Transform`] is hashed into the replay checksum.
+///
+/// Only mark entities driven by the fixed timestep, anything following
+/// frame time won't match between runs.
+#[derive(Component, Default)]
+pub struct Checksum;
+
+/// Fixed timestep ticks run since startup.
+#[derive(Resource, Default, Deref)]
+pub struct ReplayTick(u64);
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Replay {
+    pub timestep: Duration,
+    /// Ticks run until the recording stopped.
+    pub ticks: u64,
+    /// [`Checksum`] at the end of the recording.
+    pub checksum: u64,
+    /// Player actions from the tick they were first held at.
+    pub actions: Vec<(u64, ActionSnapshot)>,
+    /// Pointer input sent in each frame that had any, tagged by the ticks run
+    /// before it.
+    pub pointers: Vec<(u64, Vec<PointerInput>)>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = fs::read_to_string(path).with_context(|| format!("reading {path:?}"))?;
+        ron::from_str(&data).with_context(|| format!("parsing {path:?}"))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let data = ron::to_string(self)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("creating {parent:?}"))?;
+        }
+        fs::write(path, data).with_context(|| format!("writing {path:?}"))
+    }
+}
+
+/// The held [`PlayerAction`]s and the [`PlayerAction::Move`] axis.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct ActionSnapshot {
+    pub pressed: Vec<PlayerAction>,
+    pub movement: Vec2,
+}
+
+impl ActionSnapshot {
+    fn new(action: &ActionState<PlayerAction>) -> Self {
+        Self {
+            pressed: PlayerAction::BUTTONS
+                .into_iter()
+                .filter(|button| action.pressed(button))
+                .collect(),
+            movement: action
+                .axis_pair(&PlayerAction::Move)
+                .map_or(Vec2::ZERO, |axis| axis.xy()),
+        }
+    }
+
+    /// Writes the recorded state over the [`ActionState`], which isn't
+    /// updated from live input during playback.
+    fn apply(&self, action: &mut ActionState<PlayerAction>) {
+        for button in PlayerAction::BUTTONS {
+            let pressed = self.pressed.contains(&button);
+            let data = action.action_data_mut_or_default(&button);
+            data.state = match (pressed, data.state.pressed()) {
+                (true, true) => ButtonState::Pressed,
+                (true, false) => ButtonState::JustPressed,
+                (false, true) => ButtonState::JustReleased,
+                (false, false) => ButtonState::Released,
+            };
+            data.value = if pressed { 1.0 } else { 0.0 };
+        }
+        action
+            .action_data_mut_or_default(&PlayerAction::Move)
+            .axis_pair = Some(DualAxisData::from_xy(self.movement));
+    }
+}
+
+/// An [`InputMove`] or [`InputPress`] sent by the input plugin. Moves are
+/// replayed onto the primary canvas.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum PointerInput {
+    Move {
+        pointer: ReplayPointer,
+        position: Vec2,
+        delta: Vec2,
+    },
+    Press {
+        pointer: ReplayPointer,
+        button: ReplayButton,
+        down: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ReplayPointer {
+    Mouse,
+    Touch(u64),
+    Custom(u64, u64),
+}
+
+impl From<PointerId> for ReplayPointer {
+    fn from(id: PointerId) -> Self {
+        match id {
+            PointerId::Mouse => ReplayPointer::Mouse,
+            PointerId::Touch(id) => ReplayPointer::Touch(id),
+            PointerId::Custom(uuid) => {
+                let (high, low) = uuid.as_u64_pair();
+                ReplayPointer::Custom(high, low)
+            }
+        }
+    }
+}
+
+impl From<ReplayPointer> for PointerId {
+    fn from(pointer: ReplayPointer) -> Self {
+        match pointer {
+            ReplayPointer::Mouse => PointerId::Mouse,
+            ReplayPointer::Touch(id) => PointerId::Touch(id),
+            ReplayPointer::Custom(high, low) => PointerId::Custom(Uuid::from_u64_pair(high, low)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ReplayButton {
+    Primary,
+    Secondary,
+    Middle,
+}
+
+impl From<PointerButton> for ReplayButton {
+    fn from(button: PointerButton) -> Self {
+        match button {
+            PointerButton::Primary => ReplayButton::Primary,
+            PointerButton::Secondary => ReplayButton::Secondary,
+            PointerButton::Middle => ReplayButton::Middle,
+        }
+    }
+}
+
+impl From<ReplayButton> for PointerButton {
+    fn from(button: ReplayButton) -> Self {
+        match button {
+            ReplayButton::Primary => PointerButton::Primary,
+            ReplayButton::Secondary => PointerButton::Secondary,
+            ReplayButton::Middle => PointerButton::Middle,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Recorder {
+    path: PathBuf,
+    replay: Replay,
+}
+
+#[derive(Resource)]
+struct Playback {
+    replay: Replay,
+    next_action: usize,
+    next_pointer: usize,
+}
+
+impl Playback {
+    fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_action: 0,
+            next_pointer: 0,
+        }
+    }
+
+    fn finished(&self, tick: u64) -> bool {
+        tick >= self.replay.ticks
+    }
+}
+
+fn count_tick(mut tick: ResMut<ReplayTick>) {
+    tick.0 += 1;
+}
+
+/// Hashes the [`Transform`] of every [`Checksum`] entity, in spawn order.
+fn checksum<'a>(entities: impl Iterator<Item = (Entity, &'a Transform)>) -> u64 {
+    let mut entities: Vec<_> = entities.collect();
+    entities.sort_by_key(|(entity, _)| *entity);
+
+    let mut hasher = DefaultHasher::new();
+    for (entity, transform) in entities {
+        entity.hash(&mut hasher);
+        let Transform {
+            translation,
+            rotation,
+            scale,
+        } = transform;
+        for value in translation
+            .to_array()
+            .into_iter()
+            .chain(rotation.to_array())
+            .chain(scale.to_array())
+        {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+fn record_actions(
+    tick: Res<ReplayTick>,
+    mut recorder: ResMut<Recorder>,
+    actions: Query<&ActionState<PlayerAction>, With<Player>>,
+) {
+    let Ok(action) = actions.get_single() else {
+        return;
+    };
+
+    let snapshot = ActionSnapshot::new(action);
+    let actions = &mut recorder.replay.actions;
+    if actions.last().is_none_or(|(_, last)| *last != snapshot) {
+        actions.push((**tick, snapshot));
+    }
+}
+
+fn record_pointers(
+    tick: Res<ReplayTick>,
+    mut recorder: ResMut<Recorder>,
+    mut moves: EventReader<InputMove>,
+    mut presses: EventReader<InputPress>,
+) {
+    let inputs: Vec<_> = moves
+        .read()
+        .map(|input| PointerInput::Move {
+            pointer: input.pointer_id.into(),
+            position: input.location.position,
+            delta: input.delta,
+        })
+        .chain(presses.read().map(|input| PointerInput::Press {
+            pointer: input.pointer_id.into(),
+            button: input.button.into(),
+            down: input.direction == PressDirection::Down,
+        }))
+        .collect();
+    if !inputs.is_empty() {
+        recorder.replay.pointers.push((**tick, inputs));
+    }
+}
+
+fn save_on_exit(
+    mut exit: EventReader<AppExit>,
+    tick: Res<ReplayTick>,
+    time: Res<Time<Fixed>>,
+    mut recorder: ResMut<Recorder>,
+    entities: Query<(Entity, &Transform), With<Checksum>>,
+) {
+    if exit.read().last().is_none() {
+        return;
+    }
+
+    let recorder = &mut *recorder;
+    recorder.replay.timestep = time.timestep();
+    recorder.replay.ticks = **tick;
+    recorder.replay.checksum = checksum(entities.iter());
+    match recorder.replay.save(&recorder.path) {
+        Ok(()) => info!("saved replay to {}", recorder.path.display()),
+        Err(error) => error!("failed to save replay: {error:?}"),
+    }
+}
+
+/// Removes the player's [`InputMap`] so live input doesn't mix into the
+/// replayed actions, also after rebinding.
+fn detach_live_input(
+    mut commands: Commands,
+    players: Query<Entity, (With<Player>, With<InputMap<PlayerAction>>)>,
+) {
+    for player in &players {
+        commands.entity(player).remove::<InputMap<PlayerAction>>();
+    }
+}
+
+fn play_actions(
+    tick: Res<ReplayTick>,
+    mut playback: ResMut<Playback>,
+    mut actions: Query<&mut ActionState<PlayerAction>, With<Player>>,
+) {
+    let playback = &mut *playback;
+    let recorded = &playback.replay.actions;
+    while recorded
+        .get(playback.next_action + 1)
+        .is_some_and(|(at, _)| *at <= **tick)
+    {
+        playback.next_action += 1;
+    }
+
+    let released = ActionSnapshot::default();
+    let snapshot = match recorded.get(playback.next_action) {
+        Some((at, snapshot)) if *at <= **tick && !playback.finished(**tick) => snapshot,
+        _ => &released,
+    };
+    for mut action in &mut actions {
+        snapshot.apply(&mut action);
+    }
+}
+
+/// Replaces the pointer input sent by the input plugins with the next
+/// recorded frame of it, once its tick is reached.
+///
+/// Recorded frames are played one per frame, so the moves and presses of
+/// frames that ran in the same tick aren't merged.
+fn play_pointers(
+    tick: Res<ReplayTick>,
+    canvas: Res<CanvasHandle>,
+    mut playback: ResMut<Playback>,
+    mut moves: ResMut<Events<InputMove>>,
+    mut presses: ResMut<Events<InputPress>>,
+) {
+    // The input plugins run before, live input would mix into the replay.
+    moves.clear();
+    presses.clear();
+
+    let Some((_, inputs)) = playback
+        .replay
+        .pointers
+        .get(playback.next_pointer)
+        .filter(|(at, _)| *at <= **tick)
+    else {
+        return;
+    };
+
+    for input in inputs {
+        match *input {
+            PointerInput::Move {
+                pointer,
+                position,
+                delta,
+            } => {
+                let location = Location {
+                    target: NormalizedRenderTarget::Image((**canvas).clone()),
+                    position,
+                };
+                moves.send(InputMove::new(pointer.into(), location, delta));
+            }
+            PointerInput::Press {
+                pointer,
+                button,
+                down: true,
+            } => {
+                presses.send(InputPress::new_down(pointer.into(), button.into()));
+            }
+            PointerInput::Press {
+                pointer,
+                button,
+                down: false,
+            } => {
+                presses.send(InputPress::new_up(pointer.into(), button.into()));
+            }
+        }
+    }
+    playback.next_pointer += 1;
+}
+
+fn verify(
+    tick: Res<ReplayTick>,
+    playback: Res<Playback>,
+    entities: Query<(Entity, &Transform), With<Checksum>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !playback.finished(**tick) {
+        return;
+    }
+
+    let checksum = checksum(entities.iter());
+    if checksum == playback.replay.checksum {
+        info!("replay verified after {} ticks", **tick);
+        exit.send(AppExit::Success);
+    } else {
+        error!(
+            "replay diverged: checksum {checksum:#x} after {} ticks, recorded {:#x}",
+            **tick, playback.replay.checksum
+        );
+        exit.send(AppExit::error());
+    }
+}
+
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+enum ReplayAction {
+    Pause,
+    Step,
+    Slower,
+    Faster,
+}
+
+#[derive(Component)]
+struct ReplayHud;
+
+fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(InputManagerBundle::with_map(InputMap::new([
+        (ReplayAction::Pause, KeyCode::F5),
+        (ReplayAction::Step, KeyCode::F6),
+        (ReplayAction::Slower, KeyCode::F7),
+        (ReplayAction::Faster, KeyCode::F8),
+    ])));
+
+    commands.spawn((
+        ReplayHud,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/x12y12pxMaruMinya.ttf"),
+                font_size: 12.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(2.0),
+            left: Val::Px(2.0),
+            ..default()
+        }),
+    ));
+}
+
+/// Pauses, steps a single tick or halves and doubles the playback speed, and
+/// pauses once the replay ends.
+fn control_playback(
+    mut commands: Commands,
+    tick: Res<ReplayTick>,
+    playback: Res<Playback>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    action_state: Query<&ActionState<ReplayAction>>,
+) {
+    if playback.finished(**tick) {
+        virtual_time.pause();
+        return;
+    }
+
+    for action in &action_state {
+        if action.just_pressed(&ReplayAction::Pause) {
+            if virtual_time.is_paused() {
+                virtual_time.unpause();
+            } else {
+                virtual_time.pause();
+            }
+        }
+        if action.just_pressed(&ReplayAction::Step) && virtual_time.is_paused() {
+            commands.add(step_tick);
+        }
+        if action.just_pressed(&ReplayAction::Slower) {
+            let speed = virtual_time.relative_speed() / 2.0;
+            virtual_time.set_relative_speed(speed.max(0.125));
+        }
+        if action.just_pressed(&ReplayAction::Faster) {
+            let speed = virtual_time.relative_speed() * 2.0;
+            virtual_time.set_relative_speed(speed.min(8.0));
+        }
+    }
+}
+
+/// Runs [`FixedMain`] once while paused, with the fixed clock advanced by a
+/// timestep like [`run_fixed_main_schedule`](bevy::time::run_fixed_main_schedule)
+/// does.
+fn step_tick(world: &mut World) {
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+    world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+    *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+    world.run_schedule(FixedMain);
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+}
+
+fn update_hud(
+    tick: Res<ReplayTick>,
+    playback: Res<Playback>,
+    virtual_time: Res<Time<Virtual>>,
+    mut hud: Query<&mut Text, With<ReplayHud>>,
+) {
+    let state = if playback.finished(**tick) {
+        "end"
+    } else if virtual_time.is_paused() {
+        "paused"
+    } else {
+        "playing"
+    };
+    let value = format!(
+        "{} / {} x{} {state}\nF5 pause F6 step F7/F8 speed",
+        **tick,
+        playback.replay.ticks,
+        virtual_time.relative_speed()
+    );
+
+    for mut text in &mut hud {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base_retro::{canvas::CanvasConfig, testing::CanvasTestApp};
+
+    use super::*;
+
+    fn replay() -> Replay {
+        Replay {
+            timestep: Duration::from_micros(15625),
+            ticks: 3,
+            checksum: 0x1234,
+            actions: vec![(
+                1,
+                ActionSnapshot {
+                    pressed: vec![PlayerAction::Shoot],
+                    movement: Vec2::new(0.5, -1.0),
+                },
+            )],
+            pointers: vec![(
+                2,
+                vec![PointerInput::Press {
+                    pointer: ReplayPointer::Mouse,
+                    button: ReplayButton::Primary,
+                    down: true,
+                }],
+            )],
+        }
+    }
+
+    #[test]
+    fn replays_round_trip_through_files() {
+        let path = std::env::temp_dir()
+            .join(format!("replay-{}", std::process::id()))
+            .join("round_trip.ron");
+        let replay = replay();
+
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded.timestep, replay.timestep);
+        assert_eq!(loaded.ticks, replay.ticks);
+        assert_eq!(loaded.checksum, replay.checksum);
+        assert_eq!(loaded.actions, replay.actions);
+        assert!(matches!(
+            &loaded.pointers[..],
+            [(2, inputs)] if matches!(
+                inputs[..],
+                [PointerInput::Press {
+                    pointer: ReplayPointer::Mouse,
+                    button: ReplayButton::Primary,
+                    down: true,
+                }]
+            )
+        ));
+    }
+
+    /// Runs [`verify`] on a finished replay with one [`Checksum`] entity.
+    fn verify_exit(recorded: impl FnOnce(u64) -> u64) -> Vec<AppExit> {
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0);
+        let mut app = App::new();
+        app.add_event::<AppExit>()
+            .insert_resource(ReplayTick(3))
+            .add_systems(Update, verify);
+        let entity = app.world_mut().spawn((Checksum, transform)).id();
+
+        let mut replay = replay();
+        replay.checksum = recorded(checksum([(entity, &transform)].into_iter()));
+        app.insert_resource(Playback::new(replay));
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<Events<AppExit>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn matching_checksums_verify() {
+        assert_eq!(verify_exit(|checksum| checksum), [AppExit::Success]);
+    }
+
+    #[test]
+    fn checksum_mismatches_fail_verification() {
+        let exits = verify_exit(|checksum| checksum ^ 1);
+        assert_eq!(exits.len(), 1);
+        assert!(exits[0].is_error());
+    }
+
+    #[test]
+    fn snapshots_press_once_and_hold() {
+        let snapshot = ActionSnapshot {
+            pressed: vec![PlayerAction::Shoot],
+            movement: Vec2::X,
+        };
+        let mut action = ActionState::<PlayerAction>::default();
+
+        snapshot.apply(&mut action);
+        assert!(action.just_pressed(&PlayerAction::Shoot));
+        snapshot.apply(&mut action);
+        assert!(action.pressed(&PlayerAction::Shoot));
+        assert!(!action.just_pressed(&PlayerAction::Shoot));
+        assert_eq!(
+            action.axis_pair(&PlayerAction::Move).map(|axis| axis.xy()),
+            Some(Vec2::X)
+        );
+
+        ActionSnapshot::default().apply(&mut action);
+        assert!(action.just_released(&PlayerAction::Shoot));
+    }
+
+    #[test]
+    fn pointer_frames_replay_one_per_frame_without_live_input() {
+        let path = std::env::temp_dir()
+            .join(format!("replay-{}", std::process::id()))
+            .join("pointers.ron");
+        let mouse_move = |x: f32| PointerInput::Move {
+            pointer: ReplayPointer::Mouse,
+            position: Vec2::splat(x),
+            delta: Vec2::ZERO,
+        };
+        let press = |down| PointerInput::Press {
+            pointer: ReplayPointer::Mouse,
+            button: ReplayButton::Primary,
+            down,
+        };
+        // Three frames recorded within the same tick.
+        let replay = Replay {
+            timestep: Duration::from_secs_f32(1.0 / 60.0),
+            ticks: 1000,
+            pointers: vec![
+                (10, vec![mouse_move(10.0)]),
+                (10, vec![press(true)]),
+                (10, vec![mouse_move(20.0), press(false)]),
+            ],
+            ..default()
+        };
+        replay.save(&path).unwrap();
+        let mut app = CanvasTestApp::with_plugins(
+            CanvasConfig::default(),
+            960.0,
+            540.0,
+            ReplayPlugin(ReplayMode::Verify(path.clone())),
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let mut frames = Vec::new();
+        for _ in 0..30 {
+            // Live input, dropped while the replay plays.
+            app.move_cursor(Vec2::new(300.0, 200.0));
+            let moves: Vec<_> = app
+                .input_moves()
+                .iter()
+                .map(|input| input.location.position)
+                .collect();
+            let presses: Vec<_> = app
+                .input_presses()
+                .iter()
+                .map(|input| input.direction == PressDirection::Down)
+                .collect();
+            if !moves.is_empty() || !presses.is_empty() {
+                frames.push((moves, presses));
+            }
+        }
+
+        assert_eq!(
+            frames,
+            [
+                (vec![Vec2::splat(10.0)], vec![]),
+                (vec![], vec![true]),
+                (vec![Vec2::splat(20.0)], vec![false]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use base_config::prelude::*;
+use base_retro::{canvas::CanvasConfig, prelude::*};
+use bevy::{
+    input::touch::{TouchInput, TouchPhase},
+    prelude::*,
+    utils::{HashMap, HashSet},
+    window::PrimaryWindow,
+};
+use bevy_vector_shapes::prelude::*;
+use leafwing_input_manager::{
+    axislike::DualAxisData, buttonlike::ButtonState, plugin::InputManagerSystem, prelude::*,
+};
+
+use crate::{
+    context::{InputContext, InputContexts},
+    player::{Player, PlayerAction},
+};
+
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchControls>()
+            .init_resource::<TouchState>()
+            .init_resource::<InputContexts>()
+            .add_systems(
+                PreUpdate,
+                (toggle_controls, track_touches, feed_actions)
+                    .chain()
+                    .after(InputManagerSystem::Update),
+            )
+            .add_systems(Update, draw_controls);
+    }
+}
+
+/// On-screen controls driving the [`Player`] on touch devices, drawn at
+/// window resolution. Sizes and offsets are in logical window pixels.
+#[derive(Resource, Clone, Debug)]
+pub struct TouchControls {
+    pub stick: StickMode,
+    /// How far the knob moves from the stick center at full tilt.
+    pub stick_radius: f32,
+    pub buttons: Vec<TouchButton>,
+    /// Shown on the first touch and hidden again on keyboard or gamepad
+    /// input.
+    pub visible: bool,
+}
+
+impl Default for TouchControls {
+    fn default() -> Self {
+        Self {
+            stick: StickMode::Floating,
+            stick_radius: 48.0,
+            buttons: vec![TouchButton {
+                action: PlayerAction::Shoot,
+                offset: Vec2::new(80.0, 80.0),
+                radius: 32.0,
+            }],
+            visible: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StickMode {
+    /// Centered wherever a touch starts on the left half of the window.
+    Floating,
+    /// Centered at this offset from the bottom left corner of the window,
+    /// grabbed by any touch on the left half of the window.
+    Fixed(Vec2),
+}
+
+/// A round button holding `action` while touched.
+#[derive(Clone, Debug)]
+pub struct TouchButton {
+    pub action: PlayerAction,
+    /// Offset of the center from the bottom right corner of the window.
+    pub offset: Vec2,
+    pub radius: f32,
+}
+
+impl TouchButton {
+    fn center(&self, window: Vec2) -> Vec2 {
+        window - self.offset
+    }
+}
+
+struct StickTouch {
+    id: u64,
+    center: Vec2,
+    position: Vec2,
+}
+
+#[derive(Resource, Default)]
+struct TouchState {
+    stick: Option<StickTouch>,
+    /// Index of the button held by each touch.
+    buttons: HashMap<u64, usize>,
+    /// Actions held by touches in the last frame.
+    held: HashSet<PlayerAction>,
+}
+
+impl TouchState {
+    /// The stick tilt, y up, at most one long.
+    fn tilt(&self, radius: f32) -> Option<Vec2> {
+        let stick = self.stick.as_ref()?;
+        let offset = (stick.position - stick.center) / radius;
+        Some(Vec2::new(offset.x, -offset.y).clamp_length_max(1.0))
+    }
+}
+
+fn toggle_controls(
+    mut controls: ResMut<TouchControls>,
+    mut state: ResMut<TouchState>,
+    mut touches: EventReader<TouchInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    if touches.read().count() > 0 {
+        controls.visible = true;
+    } else if keys.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+    {
+        controls.visible = false;
+        *state = default();
+    }
+}
+
+fn track_touches(
+    controls: Res<TouchControls>,
+    mut state: ResMut<TouchState>,
+    mut touches: EventReader<TouchInput>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let size = window.size();
+
+    for touch in touches.read() {
+        match touch.phase {
+            TouchPhase::Started => {
+                let button = controls.buttons.iter().position(|button| {
+                    touch.position.distance(button.center(size)) <= button.radius
+                });
+                if let Some(button) = button {
+                    state.buttons.insert(touch.id, button);
+                } else if state.stick.is_none() && touch.position.x < size.x / 2.0 {
+                    let center = match controls.stick {
+                        StickMode::Floating => touch.position,
+                        StickMode::Fixed(offset) => Vec2::new(offset.x, size.y - offset.y),
+                    };
+                    state.stick = Some(StickTouch {
+                        id: touch.id,
+                        center,
+                        position: touch.position,
+                    });
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some(stick) = state.stick.as_mut().filter(|stick| stick.id == touch.id) {
+                    stick.position = touch.position;
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Canceled => {
+                state.buttons.remove(&touch.id);
+                if state
+                    .stick
+                    .as_ref()
+                    .is_some_and(|stick| stick.id == touch.id)
+                {
+                    state.stick = None;
+                }
+            }
+        }
+    }
+}
+
+/// Holds the touched buttons and tilts [`PlayerAction::Move`] after the input
+/// manager read the keyboard and gamepads.
+///
+/// The input manager releases the buttons again on every update, as no key
+/// holds them, so their state is written directly to be just pressed only on
+/// the first frame they are touched. Outside of gameplay the actions are
+/// left alone, like the input manager does.
+fn feed_actions(
+    controls: Res<TouchControls>,
+    mut state: ResMut<TouchState>,
+    contexts: Res<InputContexts>,
+    mut actions: Query<&mut ActionState<PlayerAction>, With<Player>>,
+) {
+    if !controls.visible || !contexts.is_active(InputContext::Gameplay) {
+        state.held.clear();
+        return;
+    }
+    let held: HashSet<_> = state
+        .buttons
+        .values()
+        .map(|&button| controls.buttons[button].action)
+        .collect();
+
+    for mut action in &mut actions {
+        for button in &held {
+            let data = action.action_data_mut_or_default(button);
+            if data.state.pressed() {
+                continue;
+            }
+            data.state = if state.held.contains(button) {
+                ButtonState::Pressed
+            } else {
+                ButtonState::JustPressed
+            };
+            data.value = 1.0;
+        }
+        if let Some(tilt) = state.tilt(controls.stick_radius) {
+            action
+                .action_data_mut_or_default(&PlayerAction::Move)
+                .axis_pair = Some(DualAxisData::from_xy(tilt));
+        }
+    }
+    state.held = held;
+}
+
+fn draw_controls(
+    mut painter: ShapePainter,
+    controls: Res<TouchControls>,
+    state: Res<TouchState>,
+    config: Res<CanvasConfig>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<WindowCamera>>,
+) {
+    if !controls.visible {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), camera.get_single())
+    else {
+        return;
+    };
+    let size = window.size();
+    // World units are canvas pixels, scaled to the window by the camera.
+    let scale = config.resolve_scale(size.x, size.y).x;
+
+    painter.render_layers = Some(WINDOW_LAYER);
+    painter.hollow = true;
+    painter.thickness = 2.0;
+    let mut circle = |position: Vec2, radius: f32, alpha: f32| {
+        if let Some(world) = camera.viewport_to_world_2d(camera_transform, position) {
+            painter.color = Color::srgba(1.0, 1.0, 1.0, alpha);
+            painter.set_translation(world.extend(10.0));
+            painter.circle(radius * scale);
+        }
+    };
+
+    for (index, button) in controls.buttons.iter().enumerate() {
+        let held = state.buttons.values().any(|&held| held == index);
+        circle(
+            button.center(size),
+            button.radius,
+            if held { 0.8 } else { 0.4 },
+        );
+    }
+
+    let (center, knob) = match (&state.stick, controls.stick) {
+        (Some(stick), _) => {
+            let tilt = state.tilt(controls.stick_radius).unwrap_or_default();
+            let knob = stick.center + Vec2::new(tilt.x, -tilt.y) * controls.stick_radius;
+            (stick.center, knob)
+        }
+        (None, StickMode::Fixed(offset)) => {
+            let center = Vec2::new(offset.x, size.y - offset.y);
+            (center, center)
+        }
+        (None, StickMode::Floating) => return,
+    };
+    circle(center, controls.stick_radius, 0.4);
+    circle(knob, controls.stick_radius / 2.0, 0.8);
+}
+
+#[cfg(test)]
+mod tests {
+    use base_retro::testing::CanvasTestApp;
+    use bevy_vector_shapes::painter::PainterPlugin;
+
+    use super::*;
+
+    fn app() -> CanvasTestApp {
+        let mut app = CanvasTestApp::with_plugins(
+            CanvasConfig::default(),
+            960.0,
+            540.0,
+            (
+                InputManagerPlugin::<PlayerAction>::default(),
+                // The shapes are only drawn, there is no renderer to add.
+                |app: &mut App| {
+                    app.insert_resource(BaseShapeConfig(ShapeConfig::default_2d()));
+                },
+                PainterPlugin,
+                TouchControlsPlugin,
+            ),
+        );
+        app.app.world_mut().spawn((
+            Player,
+            InputManagerBundle::with_map(InputMap::<PlayerAction>::default()),
+        ));
+        app.update();
+        app
+    }
+
+    fn action(app: &mut CanvasTestApp) -> ActionState<PlayerAction> {
+        app.app
+            .world_mut()
+            .query_filtered::<&ActionState<PlayerAction>, With<Player>>()
+            .single(app.app.world())
+            .clone()
+    }
+
+    #[test]
+    fn held_buttons_are_just_pressed_once() {
+        let mut app = app();
+        let shoot = TouchControls::default().buttons[0].center(Vec2::new(960.0, 540.0));
+
+        app.touch(0, TouchPhase::Started, shoot);
+        let mut just_pressed = 0;
+        for _ in 0..5 {
+            let action = action(&mut app);
+            assert!(action.pressed(&PlayerAction::Shoot));
+            just_pressed += action.just_pressed(&PlayerAction::Shoot) as usize;
+            app.update();
+        }
+        assert_eq!(just_pressed, 1);
+
+        app.touch(0, TouchPhase::Ended, shoot);
+        assert!(action(&mut app).just_released(&PlayerAction::Shoot));
+        app.update();
+        assert!(action(&mut app).released(&PlayerAction::Shoot));
+    }
+}
@@ -1,17 +1,31 @@
+use std::time::Duration;
+
 use avian2d::prelude::*;
 use base_config::prelude::*;
-use base_core::prelude::*;
+use base_core::{camera::CanvasCameraController, prelude::*};
 use base_retro::{
-    canvas::{CanvasConfig, CanvasScale},
+    canvas::{CanvasCamera, CanvasConfig, CanvasScale, CanvasSetup},
+    prelude::*,
+};
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    asset::AssetMetaCheck,
+    audio::AudioPlugin,
+    gilrs::GilrsPlugin,
     prelude::*,
+    render::{pipelined_rendering::PipelinedRenderingPlugin, settings::WgpuSettings, RenderPlugin},
+    window::ExitCondition,
+    winit::WinitPlugin,
 };
-use bevy::{asset::AssetMetaCheck, prelude::*};
 use bevy_defer::AsyncPlugin;
 use bevy_mod_picking::prelude::*;
 use bevy_vector_shapes::prelude::*;
 
 fn main() {
     let mut app = App::new();
+    let replay = ReplayMode::from_args();
+    let headless = matches!(replay, Some(ReplayMode::Verify(_)));
 
     if cfg!(not(debug_assertions)) {
         app.add_plugins(bevy_embedded_assets::EmbeddedAssetPlugin {
@@ -19,26 +33,54 @@ fn main() {
         });
     }
 
-    if cfg!(all(debug_assertions, not(target_arch = "wasm32"))) {
-        app.add_plugins(QuickClosePlugin);
+    if cfg!(all(debug_assertions, not(target_arch = "wasm32"))) && !headless {
+        app.add_plugins((QuickClosePlugin, CapturePlugin));
     }
 
-    app.add_plugins((
-        DefaultPlugins
+    let mut default_plugins = DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: Some(Window {
+                resolution: (CANVAS_SIZE * CANVAS_SCALE).into(),
+                position: WindowPosition::Centered(MonitorSelection::Current),
+                fit_canvas_to_parent: true,
+                ..default()
+            }),
+            ..default()
+        })
+        .set(AssetPlugin {
+            meta_check: AssetMetaCheck::Never,
+            ..default()
+        })
+        .set(ImagePlugin::default_nearest());
+
+    // Verifying a replay runs without a window or GPU, as fast as it can.
+    if headless {
+        default_plugins = default_plugins
+            .disable::<WinitPlugin>()
+            .disable::<AudioPlugin>()
+            .disable::<GilrsPlugin>()
+            .disable::<PipelinedRenderingPlugin>()
+            .set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    backends: None,
+                    ..default()
+                }
+                .into(),
+                ..default()
+            })
             .set(WindowPlugin {
                 primary_window: Some(Window {
                     resolution: (CANVAS_SIZE * CANVAS_SCALE).into(),
-                    position: WindowPosition::Centered(MonitorSelection::Current),
-                    fit_canvas_to_parent: true,
                     ..default()
                 }),
-                ..default()
-            })
-            .set(AssetPlugin {
-                meta_check: AssetMetaCheck::Never,
-                ..default()
-            })
-            .set(ImagePlugin::default_nearest()),
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            });
+        app.add_plugins(ScheduleRunnerPlugin::run_loop(Duration::ZERO));
+    }
+
+    app.add_plugins((
+        default_plugins,
         PhysicsPlugins::default(),
         DefaultPickingPlugins
             .build()
@@ -54,7 +96,19 @@ fn main() {
         }),
         AsyncPlugin::default_settings(),
     ))
-    .add_plugins((CanvasPlugin, MenuPlugin, PlayerPlugin, EnemyPlugin))
+    .add_plugins((
+        CanvasPlugin,
+        CameraPlugin,
+        FocusPlugin,
+        CursorPlugin,
+        DragPlugin,
+        MenuPlugin,
+        BindingsPlugin,
+        PlayerPlugin,
+        TouchControlsPlugin,
+        EnemyPlugin,
+    ))
+    .add_systems(Startup, setup_camera.after(CanvasSetup))
     .insert_resource(DebugPickingMode::Disabled)
     .insert_resource(Gravity(Vec2::NEG_Y * 500.0))
     .insert_resource(CanvasConfig {
@@ -64,6 +118,20 @@ fn main() {
         },
         clear_color: ClearColorConfig::Custom(Color::BLACK),
         ..default()
-    })
-    .run();
+    });
+
+    if let Some(replay) = replay {
+        app.add_plugins(ReplayPlugin(replay));
+    }
+
+    app.run();
+}
+
+/// Follows the player with the canvas camera.
+fn setup_camera(mut commands: Commands, cameras: Query<Entity, With<CanvasCamera>>) {
+    for camera in &cameras {
+        commands
+            .entity(camera)
+            .insert(CanvasCameraController::default());
+    }
 }
@@ -0,0 +1,32 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// Canvas pixels sampled on each side of the center, per axis.
+const TAPS: i32 = 4;
+
+@group(2) @binding(0) var<uniform> tint: vec4<f32>;
+@group(2) @binding(1) var canvas_texture: texture_2d<f32>;
+@group(2) @binding(2) var<uniform> crop: vec4<f32>;
+@group(2) @binding(3) var<uniform> radius: f32;
+
+// Gaussian weighted average of a grid of canvas pixels spread over `radius`
+// canvas pixels around the one under the fragment.
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let resolution = vec2<f32>(textureDimensions(canvas_texture));
+    let center = mix(crop.xy, crop.zw, mesh.uv) * resolution;
+    let spread = max(radius, 0.001);
+
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var y = -TAPS; y <= TAPS; y++) {
+        for (var x = -TAPS; x <= TAPS; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) / f32(TAPS) * radius;
+            let weight = exp(-2.0 * dot(offset, offset) / (spread * spread));
+            let pixel = vec2<u32>(clamp(center + offset, vec2<f32>(0.0), resolution - 1.0));
+            sum += textureLoad(canvas_texture, pixel, 0) * weight;
+            total += weight;
+        }
+    }
+
+    return sum / total * tint;
+}
@@ -0,0 +1,448 @@
+//! Reads the canvas back from the GPU at its native resolution to save
+//! screenshots and short clips.
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::Context;
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::{extract_resource, ExtractResource},
+        graph::CameraDriverLabel,
+        render_asset::RenderAssets,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, ImageCopyBuffer, ImageDataLayout, Maintain,
+            MapMode, TextureFormat,
+        },
+        renderer::{render_system, RenderContext, RenderDevice},
+        texture::GpuImage,
+        Render, RenderApp, RenderSet,
+    },
+    tasks::IoTaskPool,
+};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::{self, FilterType},
+    Delay, Frame, RgbaImage,
+};
+
+use super::{CanvasConfig, CanvasHandle};
+
+pub struct CanvasCapturePlugin;
+
+impl Plugin for CanvasCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CanvasCapture>()
+            .init_resource::<CanvasReadback>()
+            .add_systems(First, receive_frames)
+            .add_systems(Last, request_readback);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        app.insert_resource(CapturedFrames(Mutex::new(receiver)));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .insert_resource(FrameSender(sender))
+            .init_resource::<ReadbackTarget>()
+            .add_systems(ExtractSchedule, extract_resource::<CanvasReadback>)
+            .add_systems(
+                Render,
+                (
+                    prepare_readback.in_set(RenderSet::PrepareResources),
+                    map_readback.after(render_system).in_set(RenderSet::Render),
+                ),
+            );
+
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(CanvasReadbackLabel, CanvasReadbackNode);
+        graph.add_node_edge(CameraDriverLabel, CanvasReadbackLabel);
+    }
+}
+
+/// How [`CanvasCapture::save_recording`] writes the recorded frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipFormat {
+    /// A single looping animated GIF.
+    Gif,
+    /// A directory of `frame_0000.png`, `frame_0001.png`, ...
+    PngSequence,
+}
+
+struct Recording {
+    length: f32,
+    fps: f32,
+    last_frame: Option<f32>,
+    frames: VecDeque<(f32, Arc<RgbaImage>)>,
+}
+
+/// Saves what is drawn to the [`CanvasHandle`], before it is scaled to the
+/// window.
+///
+/// Files are written from the IO task pool, failures are logged.
+#[derive(Resource, Default)]
+pub struct CanvasCapture {
+    screenshots: Vec<(PathBuf, u32)>,
+    recording: Option<Recording>,
+}
+
+impl CanvasCapture {
+    /// Saves the next rendered canvas frame as a PNG, upscaled by the integer
+    /// `scale` (`1` for the native resolution).
+    pub fn screenshot(&mut self, path: impl Into<PathBuf>, scale: u32) {
+        self.screenshots.push((path.into(), scale.max(1)));
+    }
+
+    /// Starts keeping the last `length` seconds of the canvas, sampled at
+    /// `fps` frames per second.
+    pub fn start_recording(&mut self, length: f32, fps: f32) {
+        self.recording = Some(Recording {
+            length,
+            fps,
+            last_frame: None,
+            frames: VecDeque::new(),
+        });
+    }
+
+    /// Stops recording and drops the recorded frames.
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Writes the frames currently kept by the recording, upscaled by the
+    /// integer `scale`.
+    pub fn save_recording(&self, path: impl Into<PathBuf>, format: ClipFormat, scale: u32) {
+        let Some(recording) = &self.recording else {
+            warn!("Canvas capture is not recording");
+            return;
+        };
+
+        let path = path.into();
+        let fps = recording.fps;
+        let frames: Vec<_> = recording
+            .frames
+            .iter()
+            .map(|(_, frame)| frame.clone())
+            .collect();
+        IoTaskPool::get()
+            .spawn(async move {
+                let result = match format {
+                    ClipFormat::Gif => write_gif(&path, &frames, fps, scale),
+                    ClipFormat::PngSequence => write_png_sequence(&path, &frames, scale),
+                };
+                match result {
+                    Ok(()) => info!("Saved {} canvas frames to {:?}", frames.len(), path),
+                    Err(error) => error!("Failed to save canvas recording: {error:?}"),
+                }
+            })
+            .detach();
+    }
+
+    fn wants_frame(&self, now: f32) -> bool {
+        !self.screenshots.is_empty()
+            || self.recording.as_ref().is_some_and(|recording| {
+                recording
+                    .last_frame
+                    .is_none_or(|last| now - last >= 1.0 / recording.fps)
+            })
+    }
+}
+
+fn upscale(frame: &RgbaImage, scale: u32) -> RgbaImage {
+    if scale == 1 {
+        return frame.clone();
+    }
+    imageops::resize(
+        frame,
+        frame.width() * scale,
+        frame.height() * scale,
+        FilterType::Nearest,
+    )
+}
+
+fn create_parent(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {parent:?}"))?;
+    }
+    Ok(())
+}
+
+fn write_png(path: &Path, frame: &RgbaImage, scale: u32) -> anyhow::Result<()> {
+    create_parent(path)?;
+    upscale(frame, scale)
+        .save(path)
+        .with_context(|| format!("writing {path:?}"))
+}
+
+fn write_png_sequence(dir: &Path, frames: &[Arc<RgbaImage>], scale: u32) -> anyhow::Result<()> {
+    for (index, frame) in frames.iter().enumerate() {
+        write_png(&dir.join(format!("frame_{index:04}.png")), frame, scale)?;
+    }
+    Ok(())
+}
+
+fn write_gif(path: &Path, frames: &[Arc<RgbaImage>], fps: f32, scale: u32) -> anyhow::Result<()> {
+    create_parent(path)?;
+    let file = File::create(path).with_context(|| format!("creating {path:?}"))?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms(1000, fps.round().max(1.0) as u32);
+    encoder.encode_frames(
+        frames
+            .iter()
+            .map(|frame| Frame::from_parts(upscale(frame, scale), 0, 0, delay)),
+    )?;
+    Ok(())
+}
+
+/// A canvas frame copied back from the render world.
+struct CanvasFrame(RgbaImage);
+
+#[derive(Resource)]
+struct CapturedFrames(Mutex<Receiver<CanvasFrame>>);
+
+#[derive(Resource)]
+struct FrameSender(Sender<CanvasFrame>);
+
+/// The canvas image to copy back this frame, if any.
+#[derive(Resource, Clone, Default)]
+struct CanvasReadback(Option<Handle<Image>>);
+
+impl ExtractResource for CanvasReadback {
+    type Source = Self;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        source.clone()
+    }
+}
+
+fn request_readback(
+    capture: Res<CanvasCapture>,
+    canvas: Res<CanvasHandle>,
+    time: Res<Time<Real>>,
+    mut readback: ResMut<CanvasReadback>,
+) {
+    readback.0 = capture
+        .wants_frame(time.elapsed_seconds())
+        .then(|| canvas.clone());
+}
+
+fn receive_frames(
+    config: Res<CanvasConfig>,
+    mut capture: ResMut<CanvasCapture>,
+    frames: Option<Res<CapturedFrames>>,
+    time: Res<Time<Real>>,
+) {
+    let Some(frames) = frames else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+
+    for CanvasFrame(mut frame) in frames.0.lock().unwrap().try_iter() {
+        // Leave out the margin rendered for smooth scrolling.
+        let (width, height) = (config.resolution.x as u32, config.resolution.y as u32);
+        if frame.width() > width && frame.height() > height {
+            let (x, y) = ((frame.width() - width) / 2, (frame.height() - height) / 2);
+            frame = imageops::crop_imm(&frame, x, y, width, height).to_image();
+        }
+        let frame = Arc::new(frame);
+
+        for (path, scale) in capture.screenshots.drain(..) {
+            let frame = frame.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    match write_png(&path, &frame, scale) {
+                        Ok(()) => info!("Saved canvas screenshot to {:?}", path),
+                        Err(error) => error!("Failed to save canvas screenshot: {error:?}"),
+                    }
+                })
+                .detach();
+        }
+
+        if let Some(recording) = &mut capture.recording {
+            if recording
+                .last_frame
+                .is_some_and(|last| now - last < 1.0 / recording.fps)
+            {
+                continue;
+            }
+            recording.last_frame = Some(now);
+            recording.frames.push_back((now, frame));
+            while recording
+                .frames
+                .front()
+                .is_some_and(|(time, _)| now - time > recording.length)
+            {
+                recording.frames.pop_front();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct CanvasReadbackLabel;
+
+/// Buffer the canvas texture is copied into, with rows padded to the copy
+/// alignment.
+///
+/// The buffer is mapped without blocking the render world, so it is read on a
+/// later frame and no copy is made until then.
+#[derive(Resource, Default)]
+struct ReadbackTarget {
+    buffer: Option<Buffer>,
+    size: UVec2,
+    format: Option<TextureFormat>,
+    padded_row: usize,
+    /// Whether a copy is recorded this frame.
+    pending: bool,
+    /// Whether the buffer mapped, once it is done.
+    mapping: Option<Mutex<Receiver<bool>>>,
+}
+
+fn prepare_readback(
+    readback: Res<CanvasReadback>,
+    images: Res<RenderAssets<GpuImage>>,
+    device: Res<RenderDevice>,
+    mut target: ResMut<ReadbackTarget>,
+) {
+    target.pending = false;
+    let Some(image) = readback.0.as_ref().and_then(|handle| images.get(handle)) else {
+        return;
+    };
+    // Frames are skipped while the last copy is still being mapped.
+    if target.mapping.is_some() {
+        return;
+    }
+
+    if target.buffer.is_none()
+        || target.size != image.size
+        || target.format != Some(image.texture_format)
+    {
+        let padded_row = RenderDevice::align_copy_bytes_per_row(image.size.x as usize * 4);
+        target.buffer = Some(device.create_buffer(&BufferDescriptor {
+            label: Some("canvas_readback_buffer"),
+            size: (padded_row * image.size.y as usize) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }));
+        target.size = image.size;
+        target.format = Some(image.texture_format);
+        target.padded_row = padded_row;
+    }
+    target.pending = true;
+}
+
+struct CanvasReadbackNode;
+
+impl Node for CanvasReadbackNode {
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let target = world.resource::<ReadbackTarget>();
+        let (true, Some(buffer)) = (target.pending, &target.buffer) else {
+            return Ok(());
+        };
+        let readback = world.resource::<CanvasReadback>();
+        let Some(image) = readback
+            .0
+            .as_ref()
+            .and_then(|handle| world.resource::<RenderAssets<GpuImage>>().get(handle))
+        else {
+            return Ok(());
+        };
+
+        render_context.command_encoder().copy_texture_to_buffer(
+            image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(target.padded_row as u32),
+                    rows_per_image: None,
+                },
+            },
+            image.texture.size(),
+        );
+
+        Ok(())
+    }
+}
+
+fn map_readback(
+    mut target: ResMut<ReadbackTarget>,
+    device: Res<RenderDevice>,
+    sender: Res<FrameSender>,
+) {
+    let Some(buffer) = target.buffer.clone() else {
+        return;
+    };
+
+    if std::mem::take(&mut target.pending) {
+        let (mapped_sender, mapped) = mpsc::channel();
+        device.map_buffer(&buffer.slice(..), MapMode::Read, move |result| {
+            let _ = mapped_sender.send(result.is_ok());
+        });
+        target.mapping = Some(Mutex::new(mapped));
+    }
+    device.poll(Maintain::Poll);
+
+    let Some(mapping) = &target.mapping else {
+        return;
+    };
+    let mapped = match mapping.lock().unwrap().try_recv() {
+        Ok(mapped) => mapped,
+        Err(TryRecvError::Empty) => return,
+        Err(TryRecvError::Disconnected) => false,
+    };
+    target.mapping = None;
+    if !mapped {
+        warn!("Failed to map the canvas readback buffer");
+        return;
+    }
+
+    let row = target.size.x as usize * 4;
+    let mut data = Vec::with_capacity(row * target.size.y as usize);
+    for padded in buffer
+        .slice(..)
+        .get_mapped_range()
+        .chunks(target.padded_row)
+    {
+        data.extend_from_slice(&padded[..row]);
+    }
+    buffer.unmap();
+
+    if matches!(
+        target.format,
+        Some(TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb)
+    ) {
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    if let Some(frame) = RgbaImage::from_raw(target.size.x, target.size.y, data) {
+        let _ = sender.0.send(CanvasFrame(frame));
+    }
+}
@@ -0,0 +1,90 @@
+//! Confines or locks the OS cursor while the window has focus.
+
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow, WindowFocused},
+};
+
+use super::CanvasConfig;
+
+/// How the mouse is held to the canvas, see [`CanvasConfig::cursor_lock`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorLockMode {
+    /// The cursor moves freely, over the letterbox and out of the window.
+    #[default]
+    None,
+    /// The cursor is kept in the window, and the mouse pointer is clamped to
+    /// the edges of the primary canvas.
+    Confined,
+    /// The cursor is hidden and held in place, and mouse motion moves the
+    /// mouse pointer over the primary canvas instead. Uses the Pointer Lock
+    /// API on the web.
+    Locked,
+}
+
+impl CursorLockMode {
+    fn grab_mode(&self) -> CursorGrabMode {
+        match self {
+            CursorLockMode::None => CursorGrabMode::None,
+            // Browsers can't confine the cursor, the pointer is still clamped.
+            CursorLockMode::Confined if cfg!(target_arch = "wasm32") => CursorGrabMode::None,
+            CursorLockMode::Confined => CursorGrabMode::Confined,
+            CursorLockMode::Locked => CursorGrabMode::Locked,
+        }
+    }
+}
+
+/// Grabs the cursor on a click in the window and releases it when the window
+/// loses focus.
+///
+/// Browsers only grant a pointer lock shortly after a user gesture, so the
+/// cursor is grabbed on click rather than as soon as the window is focused.
+pub fn grab_cursor(
+    config: Res<CanvasConfig>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut focus: EventReader<WindowFocused>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    let lost_focus = focus.read().any(|event| !event.focused);
+    let wanted = config.cursor_lock.grab_mode();
+    if lost_focus || wanted == CursorGrabMode::None {
+        if window.cursor.grab_mode != CursorGrabMode::None {
+            window.cursor.grab_mode = CursorGrabMode::None;
+        }
+        return;
+    }
+
+    if window.focused && mouse.get_just_pressed().next().is_some() {
+        window.cursor.grab_mode = wanted;
+    }
+}
+
+/// Releases the lock once the browser exits it, usually on escape, so the
+/// next click requests it again.
+#[cfg(target_arch = "wasm32")]
+pub fn sync_pointer_lock(
+    mut acquired: Local<bool>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    let locked = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.pointer_lock_element())
+        .is_some();
+    // The lock is granted asynchronously, only release it after it was seen.
+    if locked {
+        *acquired = true;
+    } else if *acquired {
+        *acquired = false;
+        if window.cursor.grab_mode == CursorGrabMode::Locked {
+            window.cursor.grab_mode = CursorGrabMode::None;
+        }
+    }
+}
@@ -0,0 +1,836 @@
+use base_config::prelude::*;
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        texture::{BevyDefault, ImageSampler},
+        view::{Msaa, RenderLayers},
+    },
+    sprite::{Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
+    transform::TransformSystem,
+    window::WindowResized,
+};
+use bevy_mod_picking::prelude::*;
+
+pub use self::{
+    capture::{CanvasCapture, CanvasCapturePlugin, ClipFormat},
+    cursor_lock::CursorLockMode,
+    palette::CanvasPalette,
+    post_process::*,
+    scroll::{CanvasScroll, SCROLL_MARGIN},
+    secondary::{Canvas, CanvasCameraOf},
+};
+
+pub mod capture;
+pub mod cursor_lock;
+pub mod palette;
+pub mod post_process;
+pub mod scroll;
+pub mod secondary;
+
+pub struct CanvasPlugin;
+
+impl Plugin for CanvasPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            CANVAS_SHADER_HANDLE,
+            "post_process.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            BORDER_BLUR_SHADER_HANDLE,
+            "border_blur.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.add_plugins((
+            Material2dPlugin::<CanvasMaterial>::default(),
+            Material2dPlugin::<CanvasBlurMaterial>::default(),
+        ))
+        .init_asset::<palette::Palette>()
+        .init_asset_loader::<palette::PaletteLoader>()
+        .insert_resource(CanvasConfig::default())
+        .init_resource::<CanvasHandle>()
+        .insert_resource(Msaa::Off)
+        .add_event::<CanvasResized>()
+        .add_systems(Startup, setup_canvas.in_set(CanvasSetup))
+        .add_systems(
+            PreUpdate,
+            (
+                (
+                    fit_to_window,
+                    apply_config,
+                    apply_border,
+                    apply_post_process,
+                    palette::apply_palette,
+                    secondary::spawn_canvases,
+                    secondary::sync_canvases,
+                    secondary::fit_canvases,
+                )
+                    .chain(),
+                cursor_lock::grab_cursor,
+                #[cfg(target_arch = "wasm32")]
+                cursor_lock::sync_pointer_lock,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            scroll::snap_scroll
+                .in_set(CanvasScrollSnap)
+                .before(TransformSystem::TransformPropagate),
+        );
+
+        palette::add_presets(app);
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanvasSetup;
+
+/// Snaps the [`CanvasCamera`] to whole pixels from its [`CanvasScroll`], in
+/// [`PostUpdate`]. Move the scroll position before this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanvasScrollSnap;
+
+/// Sent when the image behind [`CanvasHandle`] is resized to follow
+/// [`CanvasConfig::resolution`], or to add or remove the [`SCROLL_MARGIN`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct CanvasResized {
+    pub old: Vec2,
+    pub new: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CanvasScale {
+    AutoFit {
+        pixel_perfect: bool,
+    },
+    Manual(f32),
+    /// The largest whole multiple of the canvas that fits in the window.
+    Integer,
+    /// Cover the whole window, cropping the canvas along one axis.
+    Fill,
+    /// Fill the window exactly, scaling each axis independently.
+    Stretch,
+    /// Fit the window, blending neighbouring canvas pixels only along their
+    /// shared edge, as if upscaled by a whole multiple first and then smoothly
+    /// scaled the rest of the way.
+    SharpBilinear,
+}
+
+impl Default for CanvasScale {
+    fn default() -> Self {
+        Self::AutoFit {
+            pixel_perfect: false,
+        }
+    }
+}
+
+impl CanvasScale {
+    /// Returns the size of a window pixel in canvas pixels, per axis, for a
+    /// canvas of `resolution` shown in a `width` by `height` window area.
+    pub fn resolve(&self, resolution: Vec2, width: f32, height: f32) -> Vec2 {
+        let fit = Vec2::new(width, height) / resolution;
+
+        match *self {
+            CanvasScale::Manual(scale) => Vec2::splat(scale),
+            CanvasScale::AutoFit { pixel_perfect } => {
+                let scale = if (width / height) < 1.0 { fit.x } else { fit.y };
+
+                Vec2::splat(
+                    1.0 / if pixel_perfect {
+                        let int = scale as u32;
+                        let next = (int).next_power_of_two();
+                        ((if next == int { int } else { next / 2 }) as f32).max(1.0)
+                    } else {
+                        scale
+                    },
+                )
+            }
+            CanvasScale::Integer => Vec2::splat(1.0 / fit.min_element().floor().max(1.0)),
+            CanvasScale::Fill => Vec2::splat(1.0 / fit.max_element()),
+            CanvasScale::Stretch => 1.0 / fit,
+            CanvasScale::SharpBilinear => Vec2::splat(1.0 / fit.min_element()),
+        }
+    }
+
+    /// Returns the whole multiple the canvas is prescaled by before the final
+    /// smooth scale, or `0.0` when the canvas is sampled without filtering.
+    pub fn resolve_prescale(&self, resolution: Vec2, width: f32, height: f32) -> f32 {
+        match self {
+            CanvasScale::SharpBilinear => (Vec2::new(width, height) / resolution)
+                .min_element()
+                .floor()
+                .max(1.0),
+            _ => 0.0,
+        }
+    }
+}
+
+/// What fills the window around the [`CanvasSprite`] when the canvas does not
+/// cover it entirely.
+#[derive(Clone)]
+pub enum CanvasBorder {
+    /// Clear the letterbox to a solid color.
+    Color(Color),
+    /// Repeat an image over the letterbox, in canvas pixels.
+    Tiled(Handle<Image>),
+    /// Stretch a copy of the canvas to cover the window, blurred over `radius`
+    /// canvas pixels and multiplied by `tint`.
+    Blurred { tint: Color, radius: f32 },
+}
+
+impl Default for CanvasBorder {
+    fn default() -> Self {
+        Self::Color(Color::BLACK)
+    }
+}
+
+/// Where the canvas sits in the window along the letterboxed axis.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum CanvasAlign {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+}
+
+#[derive(Resource)]
+pub struct CanvasConfig {
+    pub resolution: Vec2,
+    pub scale: CanvasScale,
+    pub cursor_lock: CursorLockMode,
+    pub clear_color: ClearColorConfig,
+    pub border: CanvasBorder,
+    pub align: CanvasAlign,
+    pub post_process: CanvasPostProcess,
+    pub palette: Option<CanvasPalette>,
+}
+
+impl Default for CanvasConfig {
+    fn default() -> Self {
+        Self {
+            resolution: Vec2::new(160.0, 144.0),
+            scale: CanvasScale::default(),
+            cursor_lock: default(),
+            clear_color: default(),
+            border: default(),
+            align: default(),
+            post_process: default(),
+            palette: None,
+        }
+    }
+}
+
+impl CanvasConfig {
+    /// Returns the size of a window pixel in canvas pixels, per axis.
+    pub fn resolve_scale(&self, width: f32, height: f32) -> Vec2 {
+        self.scale.resolve(self.resolution, width, height)
+    }
+
+    /// See [`CanvasScale::resolve_prescale`].
+    pub fn resolve_prescale(&self, width: f32, height: f32) -> f32 {
+        self.scale.resolve_prescale(self.resolution, width, height)
+    }
+
+    /// Makes `projection` show the window at the resolved scale.
+    pub fn fit_projection(&self, projection: &mut OrthographicProjection, width: f32, height: f32) {
+        let visible = Vec2::new(width, height) * self.resolve_scale(width, height);
+        projection.scale = 1.0;
+        projection.scaling_mode = ScalingMode::Fixed {
+            width: visible.x,
+            height: visible.y,
+        };
+    }
+}
+
+#[derive(Component)]
+pub struct WindowCamera;
+
+#[derive(Component)]
+pub struct CanvasCamera;
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct CanvasHandle(Handle<Image>);
+
+fn canvas_size(resolution: Vec2) -> Extent3d {
+    Extent3d {
+        width: resolution.x as u32,
+        height: resolution.y as u32,
+        ..default()
+    }
+}
+
+/// Creates an image a canvas camera can render to.
+fn canvas_image(resolution: Vec2) -> Image {
+    let size = canvas_size(resolution);
+
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::bevy_default(),
+            usage: TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        sampler: ImageSampler::nearest(),
+        ..default()
+    };
+
+    image.resize(size);
+    image
+}
+
+impl FromWorld for CanvasHandle {
+    fn from_world(world: &mut World) -> Self {
+        let image = canvas_image(world.resource::<CanvasConfig>().resolution);
+        let canvas = world.resource_mut::<Assets<Image>>().add(image);
+
+        Self(canvas)
+    }
+}
+
+#[derive(Component)]
+pub struct CanvasSprite;
+
+/// The canvas image presented by a window layer entity, used to route pointer
+/// input to whichever canvas is under it.
+#[derive(Component, Clone, Debug)]
+pub struct CanvasTarget(pub Handle<Image>);
+
+/// Sprite drawn behind the [`CanvasSprite`] for [`CanvasBorder::Tiled`].
+#[derive(Component)]
+pub struct CanvasBorderSprite;
+
+/// Mesh drawn behind the [`CanvasSprite`] for [`CanvasBorder::Blurred`], one
+/// unit wide and scaled to cover the window.
+#[derive(Component)]
+pub struct CanvasBorderBlur;
+
+#[allow(clippy::too_many_arguments)]
+fn setup_canvas(
+    mut commands: Commands,
+    config: Res<CanvasConfig>,
+    canvas: Res<CanvasHandle>,
+    windows: Query<&Window>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CanvasMaterial>>,
+    mut blur_materials: ResMut<Assets<CanvasBlurMaterial>>,
+) {
+    let window = windows.single();
+
+    // The handle is created before the app had a chance to replace the
+    // default config.
+    images
+        .get_mut(&**canvas)
+        .unwrap()
+        .resize(canvas_size(config.resolution));
+
+    debug_assert_eq!(CANVAS_LAYER, RenderLayers::layer(0));
+
+    commands.spawn((
+        CanvasCamera,
+        CANVAS_LAYER,
+        Camera2dBundle {
+            camera: Camera {
+                order: CANVAS_ORDER,
+                target: RenderTarget::Image(canvas.clone()),
+                clear_color: config.clear_color,
+                ..default()
+            },
+            ..default()
+        },
+        IsDefaultUiCamera,
+    ));
+
+    commands.spawn((
+        CanvasSprite,
+        CanvasTarget(canvas.clone()),
+        WINDOW_LAYER,
+        MaterialMesh2dBundle {
+            mesh: meshes.add(Rectangle::from_size(config.resolution)).into(),
+            material: materials.add(CanvasMaterial::new(&config.post_process, canvas.clone())),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.0)),
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+
+    commands.spawn((
+        CanvasBorderSprite,
+        WINDOW_LAYER,
+        SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -2.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+
+    commands.spawn((
+        CanvasBorderBlur,
+        WINDOW_LAYER,
+        MaterialMesh2dBundle {
+            mesh: meshes.add(Rectangle::from_size(Vec2::ONE)).into(),
+            material: blur_materials.add(CanvasBlurMaterial::new(canvas.clone())),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -2.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+
+    commands.spawn((
+        WindowCamera,
+        WINDOW_LAYER,
+        Camera2dBundle {
+            camera: Camera {
+                order: WINDOW_ORDER,
+                ..default()
+            },
+            projection: {
+                let mut projection = Camera2dBundle::default().projection;
+                config.fit_projection(
+                    &mut projection,
+                    window.resolution.width(),
+                    window.resolution.height(),
+                );
+                projection
+            },
+            ..default()
+        },
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_config(
+    config: Res<CanvasConfig>,
+    canvas: Res<CanvasHandle>,
+    scrolls: Query<Ref<CanvasScroll>>,
+    mut removed_scrolls: RemovedComponents<CanvasScroll>,
+    mut sprites: Query<&mut Mesh2dHandle, With<CanvasSprite>>,
+    mut projections: Query<&mut OrthographicProjection, With<WindowCamera>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut resized: EventWriter<CanvasResized>,
+    windows: Query<&Window>,
+) {
+    let scroll_changed =
+        removed_scrolls.read().count() > 0 || scrolls.iter().any(|scroll| scroll.is_added());
+    if !config.is_changed() && !scroll_changed {
+        return;
+    }
+
+    let window = windows.single();
+
+    let size = if scrolls.is_empty() {
+        config.resolution
+    } else {
+        config.resolution + SCROLL_MARGIN * 2.0
+    };
+    let old = images.get(&**canvas).unwrap().size_f32();
+    if old != size {
+        // Only touch the image when the size actually changed, so the canvas
+        // camera and anything sampling it is not needlessly invalidated.
+        images.get_mut(&**canvas).unwrap().resize(canvas_size(size));
+
+        let mut mesh = sprites.single_mut();
+        *mesh = meshes.add(Rectangle::from_size(size)).into();
+
+        resized.send(CanvasResized { old, new: size });
+    }
+
+    let mut projection = projections.single_mut();
+    config.fit_projection(
+        &mut projection,
+        window.resolution.width(),
+        window.resolution.height(),
+    );
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn apply_border(
+    mut commands: Commands,
+    mut resize_reader: EventReader<WindowResized>,
+    mut canvas_resize_reader: EventReader<CanvasResized>,
+    config: Res<CanvasConfig>,
+    canvas: Res<CanvasHandle>,
+    images: Res<Assets<Image>>,
+    windows: Query<&Window>,
+    mut cameras: Query<
+        (&mut Camera, &mut Transform, Ref<OrthographicProjection>),
+        With<WindowCamera>,
+    >,
+    mut sprites: Query<
+        (
+            Entity,
+            &mut Sprite,
+            &mut Handle<Image>,
+            &mut Visibility,
+            &mut Transform,
+        ),
+        (With<CanvasBorderSprite>, Without<WindowCamera>),
+    >,
+    mut blurs: Query<
+        (&Handle<CanvasBlurMaterial>, &mut Visibility, &mut Transform),
+        (
+            With<CanvasBorderBlur>,
+            Without<CanvasBorderSprite>,
+            Without<WindowCamera>,
+        ),
+    >,
+    mut blur_materials: ResMut<Assets<CanvasBlurMaterial>>,
+) {
+    // The blur crops the scroll margin, which is added or removed with a
+    // canvas resize.
+    let resized = resize_reader.read().count() > 0 || canvas_resize_reader.read().count() > 0;
+    let Ok((mut camera, mut camera_transform, projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    if !resized && !config.is_changed() && !projection.is_changed() {
+        return;
+    }
+
+    let window = windows.single();
+    let visible = Vec2::new(window.width(), window.height())
+        * config.resolve_scale(window.width(), window.height());
+
+    // Keep the canvas edge flush with the window edge; the canvas stays at the
+    // world origin so picking can keep mapping window space to canvas space.
+    let offset = (visible.y - config.resolution.y) / 2.0;
+    camera_transform.translation.y = match config.align {
+        CanvasAlign::Center => 0.0,
+        CanvasAlign::Top => -offset,
+        CanvasAlign::Bottom => offset,
+    };
+
+    let (
+        Ok((entity, mut sprite, mut texture, mut sprite_visibility, mut sprite_transform)),
+        Ok((blur, mut blur_visibility, mut blur_transform)),
+    ) = (sprites.get_single_mut(), blurs.get_single_mut())
+    else {
+        return;
+    };
+    for transform in [&mut sprite_transform, &mut blur_transform] {
+        transform.translation.x = camera_transform.translation.x;
+        transform.translation.y = camera_transform.translation.y;
+    }
+    camera.clear_color = ClearColorConfig::Custom(Color::BLACK);
+    *sprite_visibility = Visibility::Hidden;
+    *blur_visibility = Visibility::Hidden;
+
+    match &config.border {
+        CanvasBorder::Color(color) => {
+            camera.clear_color = ClearColorConfig::Custom(*color);
+        }
+        CanvasBorder::Tiled(image) => {
+            *sprite_visibility = Visibility::Inherited;
+            *texture = image.clone();
+            sprite.custom_size = Some(visible);
+            commands.entity(entity).insert(ImageScaleMode::Tiled {
+                tile_x: true,
+                tile_y: true,
+                stretch_value: 1.0,
+            });
+        }
+        CanvasBorder::Blurred { tint, radius } => {
+            *blur_visibility = Visibility::Inherited;
+            // Cover the window with the canvas area, keeping its aspect ratio.
+            let cover = config.resolution * (visible / config.resolution).max_element();
+            blur_transform.scale = cover.extend(1.0);
+
+            let size = images
+                .get(&**canvas)
+                .map_or(config.resolution, Image::size_f32);
+            let margin = ((size - config.resolution) / 2.0).max(Vec2::ZERO);
+            if let Some(material) = blur_materials.get_mut(blur) {
+                material.tint = LinearRgba::from(*tint).to_vec4();
+                material.radius = *radius;
+                material.crop = Vec4::new(
+                    margin.x / size.x,
+                    margin.y / size.y,
+                    (margin.x + config.resolution.x) / size.x,
+                    (margin.y + config.resolution.y) / size.y,
+                );
+            }
+        }
+    }
+}
+
+fn fit_to_window(
+    mut resize_reader: EventReader<WindowResized>,
+    config: Res<CanvasConfig>,
+    mut projections: Query<&mut OrthographicProjection, With<WindowCamera>>,
+) {
+    for resize in resize_reader.read() {
+        let mut projection = projections.single_mut();
+        config.fit_projection(&mut projection, resize.width, resize.height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::CanvasTestApp;
+
+    use super::*;
+
+    const RESOLUTION: Vec2 = Vec2::new(320.0, 180.0);
+
+    /// A canvas scaled three times in a window showing 60 more canvas pixels
+    /// vertically.
+    fn letterboxed_app() -> CanvasTestApp {
+        let config = CanvasConfig {
+            resolution: RESOLUTION,
+            scale: CanvasScale::Integer,
+            ..default()
+        };
+        CanvasTestApp::new(config, 960.0, 720.0)
+    }
+
+    fn window_camera(app: &mut CanvasTestApp) -> (ClearColorConfig, Vec3) {
+        let world = app.app.world_mut();
+        let (camera, transform) = world
+            .query_filtered::<(&Camera, &Transform), With<WindowCamera>>()
+            .single(world);
+        (camera.clear_color, transform.translation)
+    }
+
+    fn border_sprite(app: &mut CanvasTestApp) -> (Entity, Sprite, Visibility, Vec3) {
+        let world = app.app.world_mut();
+        let (entity, sprite, visibility, transform) = world
+            .query_filtered::<(Entity, &Sprite, &Visibility, &Transform), With<CanvasBorderSprite>>(
+            )
+            .single(world);
+        (entity, sprite.clone(), *visibility, transform.translation)
+    }
+
+    fn border_blur(app: &mut CanvasTestApp) -> (CanvasBlurMaterial, Visibility, Transform) {
+        let world = app.app.world_mut();
+        let (handle, visibility, transform) = world
+            .query_filtered::<(&Handle<CanvasBlurMaterial>, &Visibility, &Transform), With<CanvasBorderBlur>>()
+            .single(world);
+        let material = world
+            .resource::<Assets<CanvasBlurMaterial>>()
+            .get(handle)
+            .unwrap();
+        (material.clone(), *visibility, *transform)
+    }
+
+    #[test]
+    fn auto_fit_scales_by_the_shorter_window_axis() {
+        let scale = CanvasScale::AutoFit {
+            pixel_perfect: false,
+        };
+
+        assert_eq!(
+            scale.resolve(RESOLUTION, 1000.0, 540.0),
+            Vec2::splat(1.0 / 3.0)
+        );
+        assert_eq!(scale.resolve(RESOLUTION, 640.0, 720.0), Vec2::splat(0.5));
+    }
+
+    #[test]
+    fn pixel_perfect_auto_fit_rounds_down_to_a_power_of_two() {
+        let scale = CanvasScale::AutoFit {
+            pixel_perfect: true,
+        };
+
+        assert_eq!(scale.resolve(RESOLUTION, 960.0, 540.0), Vec2::splat(0.5));
+        assert_eq!(scale.resolve(RESOLUTION, 1280.0, 720.0), Vec2::splat(0.25));
+        assert_eq!(scale.resolve(RESOLUTION, 160.0, 90.0), Vec2::ONE);
+    }
+
+    #[test]
+    fn manual_ignores_the_window() {
+        let scale = CanvasScale::Manual(0.25);
+
+        assert_eq!(scale.resolve(RESOLUTION, 960.0, 540.0), Vec2::splat(0.25));
+        assert_eq!(scale.resolve(RESOLUTION, 100.0, 100.0), Vec2::splat(0.25));
+    }
+
+    #[test]
+    fn integer_uses_the_largest_whole_multiple_that_fits() {
+        let scale = CanvasScale::Integer;
+
+        assert_eq!(
+            scale.resolve(RESOLUTION, 1000.0, 600.0),
+            Vec2::splat(1.0 / 3.0)
+        );
+        assert_eq!(
+            scale.resolve(RESOLUTION, 1280.0, 540.0),
+            Vec2::splat(1.0 / 3.0)
+        );
+        // Never smaller than the native resolution.
+        assert_eq!(scale.resolve(RESOLUTION, 100.0, 100.0), Vec2::ONE);
+    }
+
+    #[test]
+    fn fill_covers_the_window() {
+        let scale = CanvasScale::Fill;
+
+        assert_eq!(scale.resolve(RESOLUTION, 1280.0, 540.0), Vec2::splat(0.25));
+        assert_eq!(scale.resolve(RESOLUTION, 640.0, 720.0), Vec2::splat(0.25));
+    }
+
+    #[test]
+    fn stretch_scales_each_axis() {
+        let scale = CanvasScale::Stretch;
+
+        assert_eq!(
+            scale.resolve(RESOLUTION, 1280.0, 540.0),
+            Vec2::new(0.25, 1.0 / 3.0)
+        );
+    }
+
+    #[test]
+    fn sharp_bilinear_fits_and_prescales_by_whole_multiples() {
+        let scale = CanvasScale::SharpBilinear;
+
+        assert_eq!(scale.resolve(RESOLUTION, 800.0, 450.0), Vec2::splat(0.4));
+        assert_eq!(scale.resolve_prescale(RESOLUTION, 800.0, 450.0), 2.0);
+        assert_eq!(scale.resolve_prescale(RESOLUTION, 100.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn only_sharp_bilinear_prescales() {
+        for scale in [
+            CanvasScale::default(),
+            CanvasScale::Manual(1.0),
+            CanvasScale::Integer,
+            CanvasScale::Fill,
+            CanvasScale::Stretch,
+        ] {
+            assert_eq!(scale.resolve_prescale(RESOLUTION, 800.0, 450.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn alignment_keeps_the_canvas_edge_flush_with_the_window() {
+        let mut app = letterboxed_app();
+        assert_eq!(window_camera(&mut app).1.y, 0.0);
+
+        // 240 canvas pixels are visible, the canvas edges are at 90.
+        app.config_mut().align = CanvasAlign::Top;
+        app.update();
+        assert_eq!(window_camera(&mut app).1.y, -30.0);
+        assert_eq!(border_sprite(&mut app).3.y, -30.0);
+        assert_eq!(border_blur(&mut app).2.translation.y, -30.0);
+
+        app.config_mut().align = CanvasAlign::Bottom;
+        app.update();
+        assert_eq!(window_camera(&mut app).1.y, 30.0);
+
+        // Nothing to align without a letterbox.
+        app.resize_window(960.0, 540.0);
+        assert_eq!(window_camera(&mut app).1.y, 0.0);
+    }
+
+    #[test]
+    fn color_borders_clear_the_window() {
+        let mut app = letterboxed_app();
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        app.config_mut().border = CanvasBorder::Color(red);
+        app.update();
+
+        assert!(matches!(
+            window_camera(&mut app).0,
+            ClearColorConfig::Custom(color) if color == red
+        ));
+        assert_eq!(border_sprite(&mut app).2, Visibility::Hidden);
+        assert_eq!(border_blur(&mut app).1, Visibility::Hidden);
+    }
+
+    #[test]
+    fn tiled_borders_cover_the_window() {
+        let mut app = letterboxed_app();
+        let image = app
+            .app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(Image::default());
+        app.config_mut().border = CanvasBorder::Tiled(image.clone());
+        app.update();
+
+        let (entity, sprite, visibility, _) = border_sprite(&mut app);
+        assert_eq!(visibility, Visibility::Inherited);
+        assert_eq!(sprite.custom_size, Some(Vec2::new(320.0, 240.0)));
+        let world = app.app.world();
+        assert_eq!(world.get::<Handle<Image>>(entity), Some(&image));
+        assert!(world.entity(entity).contains::<ImageScaleMode>());
+        assert_eq!(border_blur(&mut app).1, Visibility::Hidden);
+    }
+
+    #[test]
+    fn blurred_borders_cover_the_window_with_the_canvas() {
+        let mut app = letterboxed_app();
+        app.config_mut().border = CanvasBorder::Blurred {
+            tint: Color::srgb(0.5, 0.5, 0.5),
+            radius: 6.0,
+        };
+        app.update();
+
+        let (material, visibility, transform) = border_blur(&mut app);
+        assert_eq!(visibility, Visibility::Inherited);
+        assert_eq!(border_sprite(&mut app).2, Visibility::Hidden);
+        // Scaled by 4/3 to cover the 240 visible rows.
+        assert!(transform
+            .scale
+            .truncate()
+            .abs_diff_eq(Vec2::new(320.0 * 4.0 / 3.0, 240.0), 1e-3));
+        assert_eq!(material.radius, 6.0);
+        assert_eq!(
+            material.tint,
+            LinearRgba::from(Color::srgb(0.5, 0.5, 0.5)).to_vec4()
+        );
+        assert_eq!(material.crop, Vec4::new(0.0, 0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn blurred_borders_leave_out_the_scroll_margin() {
+        let mut app = letterboxed_app();
+        app.config_mut().border = CanvasBorder::Blurred {
+            tint: Color::WHITE,
+            radius: 4.0,
+        };
+        let camera = app
+            .app
+            .world_mut()
+            .query_filtered::<Entity, With<CanvasCamera>>()
+            .single(app.app.world());
+        app.app
+            .world_mut()
+            .entity_mut(camera)
+            .insert(CanvasScroll::default());
+        app.update();
+
+        let size = RESOLUTION + SCROLL_MARGIN * 2.0;
+        assert_eq!(
+            border_blur(&mut app).0.crop,
+            Vec4::new(
+                SCROLL_MARGIN / size.x,
+                SCROLL_MARGIN / size.y,
+                (SCROLL_MARGIN + RESOLUTION.x) / size.x,
+                (SCROLL_MARGIN + RESOLUTION.y) / size.y,
+            )
+        );
+        // Still covering the window with the canvas area only.
+        assert!(border_blur(&mut app)
+            .2
+            .scale
+            .truncate()
+            .abs_diff_eq(Vec2::new(320.0 * 4.0 / 3.0, 240.0), 1e-3));
+    }
+}
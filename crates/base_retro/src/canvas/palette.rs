@@ -0,0 +1,291 @@
+//! Snapping the canvas to a fixed color palette.
+
+use anyhow::{anyhow, bail, Context};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use super::{CanvasConfig, CanvasMaterial, CanvasSprite};
+
+pub const GAME_BOY: Handle<Palette> =
+    Handle::weak_from_u128(0x6d0b_3f51_8a27_4e9c_b1d4_07e2_59c3_a810);
+pub const PICO_8: Handle<Palette> =
+    Handle::weak_from_u128(0x6d0b_3f51_8a27_4e9c_b1d4_07e2_59c3_a811);
+pub const NES: Handle<Palette> = Handle::weak_from_u128(0x6d0b_3f51_8a27_4e9c_b1d4_07e2_59c3_a812);
+
+/// A list of colors the canvas can be restricted to.
+///
+/// Loaded from `.hex` files (one `RRGGBB` color per line) or GIMP `.gpl`
+/// palettes, or one of the built-in [`GAME_BOY`], [`PICO_8`] and [`NES`]
+/// handles.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct Palette {
+    pub colors: Vec<Srgba>,
+}
+
+impl Palette {
+    pub fn from_hex(source: &str) -> anyhow::Result<Self> {
+        let colors = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(';'))
+            .map(|line| Srgba::hex(line).with_context(|| format!("invalid color `{line}`")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self { colors })
+    }
+
+    pub fn from_gpl(source: &str) -> anyhow::Result<Self> {
+        let mut lines = source.lines().map(str::trim);
+        if lines.next() != Some("GIMP Palette") {
+            bail!("missing `GIMP Palette` header");
+        }
+
+        let colors = lines
+            .filter(|line| {
+                !line.is_empty()
+                    && !line.starts_with('#')
+                    && !line.starts_with("Name:")
+                    && !line.starts_with("Columns:")
+            })
+            .map(|line| {
+                let mut channels = line.split_whitespace().take(3).map(str::parse::<u8>);
+                let mut next = || {
+                    channels
+                        .next()
+                        .ok_or_else(|| anyhow!("missing channel in `{line}`"))?
+                        .with_context(|| format!("invalid channel in `{line}`"))
+                };
+                Ok(Srgba::rgb_u8(next()?, next()?, next()?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self { colors })
+    }
+
+    fn from_hex_list(colors: &[&str]) -> Self {
+        Self {
+            colors: colors.iter().map(|hex| Srgba::hex(hex).unwrap()).collect(),
+        }
+    }
+
+    /// Builds the 1 pixel high lookup texture sampled by the canvas shader.
+    fn to_image(&self) -> Image {
+        let data = self
+            .colors
+            .iter()
+            .flat_map(|color| color.to_u8_array())
+            .collect();
+
+        Image::new(
+            Extent3d {
+                width: self.colors.len().max(1) as u32,
+                height: 1,
+                ..default()
+            },
+            TextureDimension::D2,
+            if self.colors.is_empty() {
+                vec![0; 4]
+            } else {
+                data
+            },
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct PaletteLoader;
+
+impl AssetLoader for PaletteLoader {
+    type Asset = Palette;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Palette, Self::Error> {
+        let mut source = String::new();
+        reader.read_to_string(&mut source).await?;
+
+        match load_context.path().extension().and_then(|ext| ext.to_str()) {
+            Some("gpl") => Palette::from_gpl(&source),
+            _ => Palette::from_hex(&source),
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["hex", "gpl"]
+    }
+}
+
+/// Restricts every canvas pixel to the nearest color of a [`Palette`].
+///
+/// Swapping [`CanvasPalette::palette`] at runtime recolors the whole canvas,
+/// which can be used for palette swap effects.
+#[derive(Clone, Debug)]
+pub struct CanvasPalette {
+    pub palette: Handle<Palette>,
+    /// Strength of the 4x4 ordered dithering applied before snapping, `0.0`
+    /// disables it.
+    pub dither: f32,
+}
+
+impl CanvasPalette {
+    pub fn new(palette: Handle<Palette>) -> Self {
+        Self {
+            palette,
+            dither: 0.0,
+        }
+    }
+
+    pub fn with_dither(mut self, dither: f32) -> Self {
+        self.dither = dither;
+        self
+    }
+}
+
+pub(super) fn add_presets(app: &mut App) {
+    let mut palettes = app.world_mut().resource_mut::<Assets<Palette>>();
+
+    palettes.insert(
+        &GAME_BOY,
+        Palette::from_hex_list(&["0f380f", "306230", "8bac0f", "9bbc0f"]),
+    );
+    palettes.insert(
+        &PICO_8,
+        Palette::from_hex_list(&[
+            "000000", "1d2b53", "7e2553", "008751", "ab5236", "5f574f", "c2c3c7", "fff1e8",
+            "ff004d", "ffa300", "ffec27", "00e436", "29adff", "83769c", "ff77a8", "ffccaa",
+        ]),
+    );
+    palettes.insert(
+        &NES,
+        Palette::from_hex_list(&[
+            "000000", "fcfcfc", "f8f8f8", "bcbcbc", "7c7c7c", "a4e4fc", "3cbcfc", "0078f8",
+            "0000fc", "b8b8f8", "6888fc", "0058f8", "0000bc", "d8b8f8", "9878f8", "6844fc",
+            "4428bc", "f8b8f8", "f878f8", "d800cc", "940084", "f8a4c0", "f85898", "e40058",
+            "a80020", "f0d0b0", "f87858", "f83800", "a81000", "fce0a8", "fca044", "e45c10",
+            "881400", "f8d878", "f8b800", "ac7c00", "503000", "d8f878", "b8f818", "00b800",
+            "007800", "b8f8b8", "58d854", "00a800", "006800", "b8f8d8", "58f898", "00a844",
+            "005800", "00fcfc", "00e8d8", "008888", "004058", "f8d8f8", "787878",
+        ]),
+    );
+}
+
+pub(super) fn apply_palette(
+    config: Res<CanvasConfig>,
+    mut palette_events: EventReader<AssetEvent<Palette>>,
+    palettes: Res<Assets<Palette>>,
+    sprites: Query<&Handle<CanvasMaterial>, With<CanvasSprite>>,
+    mut materials: ResMut<Assets<CanvasMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut palette_image: Local<Option<(AssetId<Palette>, Handle<Image>)>>,
+) {
+    let palette_changed = palette_events
+        .read()
+        .filter(|event| {
+            config.palette.as_ref().is_some_and(|palette| {
+                event.is_loaded_with_dependencies(&palette.palette)
+                    || event.is_modified(&palette.palette)
+            })
+        })
+        .count()
+        > 0;
+    if !config.is_changed() && !palette_changed {
+        return;
+    }
+
+    let palette = config.palette.as_ref().and_then(|canvas_palette| {
+        palettes
+            .get(&canvas_palette.palette)
+            .map(|palette| (canvas_palette.palette.id(), palette, canvas_palette.dither))
+    });
+
+    // Other config changes keep the texture, palette changes refill it.
+    let built = palette_image.take();
+    let image = palette.map(|(id, palette, _)| {
+        let image = match built {
+            Some((built, image)) if built == id && !palette_changed => image,
+            Some((_, image)) => {
+                images.insert(&image, palette.to_image());
+                image
+            }
+            None => images.add(palette.to_image()),
+        };
+        *palette_image = Some((id, image.clone()));
+        image
+    });
+
+    for handle in &sprites {
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+
+        match palette {
+            Some((_, palette, dither)) => {
+                material.quantize = Vec4::new(palette.colors.len() as f32, dither, 0.0, 0.0);
+                material.palette.clone_from(&image);
+            }
+            None => {
+                material.quantize = Vec4::ZERO;
+                material.palette = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::CanvasTestApp;
+
+    use super::*;
+
+    /// The palette texture of every canvas sprite.
+    fn palette_images(app: &mut CanvasTestApp) -> Vec<Option<Handle<Image>>> {
+        let world = app.app.world_mut();
+        let handles: Vec<_> = world
+            .query_filtered::<&Handle<CanvasMaterial>, With<CanvasSprite>>()
+            .iter(world)
+            .cloned()
+            .collect();
+        let materials = world.resource::<Assets<CanvasMaterial>>();
+        handles
+            .iter()
+            .map(|handle| materials.get(handle).unwrap().palette.clone())
+            .collect()
+    }
+
+    #[test]
+    fn config_changes_reuse_the_palette_texture() {
+        let mut app = CanvasTestApp::new(CanvasConfig::default(), 960.0, 540.0);
+        app.config_mut().palette = Some(CanvasPalette::new(GAME_BOY));
+        app.update();
+        let images = app.app.world().resource::<Assets<Image>>().len();
+        let palettes = palette_images(&mut app);
+        assert!(!palettes.is_empty());
+        assert!(palettes.iter().all(Option::is_some));
+
+        for dither in [0.25, 0.5, 1.0] {
+            app.config_mut().palette = Some(CanvasPalette::new(GAME_BOY).with_dither(dither));
+            app.update();
+        }
+        assert_eq!(app.app.world().resource::<Assets<Image>>().len(), images);
+        assert_eq!(palette_images(&mut app), palettes);
+
+        // Other palettes refill the same texture.
+        app.config_mut().palette = Some(CanvasPalette::new(PICO_8));
+        app.update();
+        assert_eq!(app.app.world().resource::<Assets<Image>>().len(), images);
+        assert_eq!(palette_images(&mut app), palettes);
+    }
+}
@@ -0,0 +1,214 @@
+//! Effects applied while the canvas is drawn to the window, at window
+//! resolution.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::Material2d,
+    window::WindowResized,
+};
+
+use super::{CanvasConfig, CanvasSprite};
+
+pub(super) const CANVAS_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x2f1c_6a0e_94b3_4c1d_8e57_d0a3_b8f6_1e42);
+
+pub(super) const BORDER_BLUR_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x8d47_1b2e_c350_4f9a_b6e1_29f0_7ac4_53d8);
+
+/// The stack of effects applied to the [`CanvasSprite`].
+///
+/// Every pass can be toggled at runtime through [`CanvasConfig::post_process`].
+#[derive(Clone, Default, Debug)]
+pub struct CanvasPostProcess {
+    pub curvature: CrtCurvature,
+    pub scanlines: Scanlines,
+    pub chromatic_aberration: ChromaticAberration,
+    pub vignette: Vignette,
+}
+
+/// Bends the canvas like the glass of a CRT, clipping the corners.
+#[derive(Clone, Debug)]
+pub struct CrtCurvature {
+    pub enabled: bool,
+    /// How far the edges are pulled towards the center.
+    pub amount: f32,
+}
+
+impl Default for CrtCurvature {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            amount: 0.05,
+        }
+    }
+}
+
+/// Darkens the edges of every canvas pixel row.
+#[derive(Clone, Debug)]
+pub struct Scanlines {
+    pub enabled: bool,
+    /// How dark the gap between rows gets, from 0 to 1.
+    pub intensity: f32,
+}
+
+impl Default for Scanlines {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intensity: 0.35,
+        }
+    }
+}
+
+/// Splits the red and blue channels apart horizontally.
+#[derive(Clone, Debug)]
+pub struct ChromaticAberration {
+    pub enabled: bool,
+    /// Channel offset in canvas pixels.
+    pub offset: f32,
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            offset: 0.5,
+        }
+    }
+}
+
+/// Darkens the canvas towards its corners.
+#[derive(Clone, Debug)]
+pub struct Vignette {
+    pub enabled: bool,
+    /// How dark the corners get, from 0 to 1.
+    pub intensity: f32,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intensity: 0.3,
+        }
+    }
+}
+
+impl CanvasPostProcess {
+    /// Packs the enabled passes into the uniform read by the canvas shader.
+    ///
+    /// Disabled passes are sent as zero, which the shader treats as a no-op.
+    fn uniform(&self) -> Vec4 {
+        let pass = |enabled: bool, value: f32| if enabled { value } else { 0.0 };
+
+        Vec4::new(
+            pass(self.curvature.enabled, self.curvature.amount),
+            pass(self.scanlines.enabled, self.scanlines.intensity),
+            pass(
+                self.chromatic_aberration.enabled,
+                self.chromatic_aberration.offset,
+            ),
+            pass(self.vignette.enabled, self.vignette.intensity),
+        )
+    }
+}
+
+/// Material used by the [`CanvasSprite`] to draw the canvas to the window.
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub struct CanvasMaterial {
+    /// Curvature, scanlines, chromatic aberration and vignette strength.
+    #[uniform(0)]
+    pub effects: Vec4,
+    #[texture(1)]
+    #[sampler(2)]
+    pub canvas: Handle<Image>,
+    /// Palette color count and dithering strength, see
+    /// [`CanvasPalette`](super::CanvasPalette).
+    #[uniform(3)]
+    pub quantize: Vec4,
+    #[texture(4)]
+    pub palette: Option<Handle<Image>>,
+    /// See [`CanvasConfig::resolve_prescale`].
+    #[uniform(5)]
+    pub prescale: f32,
+    /// The visible part of the canvas image in UVs, see
+    /// [`CanvasScroll`](super::CanvasScroll).
+    #[uniform(6)]
+    pub crop: Vec4,
+}
+
+impl CanvasMaterial {
+    pub fn new(post_process: &CanvasPostProcess, canvas: Handle<Image>) -> Self {
+        Self {
+            effects: post_process.uniform(),
+            canvas,
+            quantize: Vec4::ZERO,
+            palette: None,
+            prescale: 0.0,
+            crop: Vec4::new(0.0, 0.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Material2d for CanvasMaterial {
+    fn fragment_shader() -> ShaderRef {
+        CANVAS_SHADER_HANDLE.into()
+    }
+}
+
+/// Material drawing a blurred copy of the canvas behind the [`CanvasSprite`]
+/// for [`CanvasBorder::Blurred`](super::CanvasBorder::Blurred).
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub struct CanvasBlurMaterial {
+    /// Linear color the blurred canvas is multiplied by.
+    #[uniform(0)]
+    pub tint: Vec4,
+    #[texture(1)]
+    pub canvas: Handle<Image>,
+    /// The canvas area of the canvas image in UVs, leaving out the
+    /// [`SCROLL_MARGIN`](super::SCROLL_MARGIN).
+    #[uniform(2)]
+    pub crop: Vec4,
+    /// How far the blur spreads, in canvas pixels.
+    #[uniform(3)]
+    pub radius: f32,
+}
+
+impl CanvasBlurMaterial {
+    pub fn new(canvas: Handle<Image>) -> Self {
+        Self {
+            tint: Vec4::ONE,
+            canvas,
+            crop: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            radius: 0.0,
+        }
+    }
+}
+
+impl Material2d for CanvasBlurMaterial {
+    fn fragment_shader() -> ShaderRef {
+        BORDER_BLUR_SHADER_HANDLE.into()
+    }
+}
+
+pub(super) fn apply_post_process(
+    mut resize_reader: EventReader<WindowResized>,
+    config: Res<CanvasConfig>,
+    windows: Query<&Window>,
+    sprites: Query<&Handle<CanvasMaterial>, With<CanvasSprite>>,
+    mut materials: ResMut<Assets<CanvasMaterial>>,
+) {
+    let resized = resize_reader.read().count() > 0;
+    if !resized && !config.is_changed() {
+        return;
+    }
+
+    let window = windows.single();
+    for handle in &sprites {
+        if let Some(material) = materials.get_mut(handle) {
+            material.effects = config.post_process.uniform();
+            material.prescale = config.resolve_prescale(window.width(), window.height());
+        }
+    }
+}
@@ -0,0 +1,111 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+const PI: f32 = 3.14159265;
+
+@group(2) @binding(0) var<uniform> effects: vec4<f32>;
+@group(2) @binding(1) var canvas_texture: texture_2d<f32>;
+@group(2) @binding(2) var canvas_sampler: sampler;
+@group(2) @binding(3) var<uniform> quantize: vec4<f32>;
+@group(2) @binding(4) var palette_texture: texture_2d<f32>;
+@group(2) @binding(5) var<uniform> prescale: f32;
+@group(2) @binding(6) var<uniform> crop: vec4<f32>;
+
+// Snaps a color to the nearest palette entry, after offsetting it by the
+// 4x4 Bayer threshold of its canvas pixel.
+fn snap(color: vec4<f32>, pixel: vec2<u32>) -> vec4<f32> {
+    let count = u32(quantize.x);
+    if (count == 0u) {
+        return color;
+    }
+
+    var bayer = array<f32, 16>(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    );
+    let threshold = bayer[(pixel.y % 4u) * 4u + pixel.x % 4u] / 16.0 - 0.5;
+    let target_color = color.rgb + threshold * quantize.y;
+
+    var best = textureLoad(palette_texture, vec2<u32>(0u, 0u), 0).rgb;
+    var best_distance = distance(target_color, best);
+    for (var i = 1u; i < count; i++) {
+        let entry = textureLoad(palette_texture, vec2<u32>(i, 0u), 0).rgb;
+        let entry_distance = distance(target_color, entry);
+        if (entry_distance < best_distance) {
+            best = entry;
+            best_distance = entry_distance;
+        }
+    }
+
+    return vec4<f32>(best, color.a);
+}
+
+fn load(pixel: vec2<f32>, resolution: vec2<f32>) -> vec4<f32> {
+    let clamped = vec2<u32>(clamp(pixel, vec2<f32>(0.0), resolution - 1.0));
+    return snap(textureLoad(canvas_texture, clamped, 0), clamped);
+}
+
+fn fetch(uv: vec2<f32>, resolution: vec2<f32>) -> vec4<f32> {
+    let texel = uv * resolution;
+    if (prescale == 0.0) {
+        return load(floor(texel), resolution);
+    }
+
+    // Sharp bilinear: only blend across the band that the remaining
+    // fractional scale would smooth a prescaled canvas pixel edge over.
+    // `position` is in canvas pixels with pixel centers on whole numbers.
+    let offset = fract(texel) - 0.5;
+    let region = vec2<f32>(0.5 - 0.5 / prescale);
+    let position = floor(texel) + (offset - clamp(offset, -region, region)) * prescale;
+    let base = floor(position);
+    let weight = position - base;
+
+    let top = mix(load(base, resolution), load(base + vec2<f32>(1.0, 0.0), resolution), weight.x);
+    let bottom = mix(
+        load(base + vec2<f32>(0.0, 1.0), resolution),
+        load(base + vec2<f32>(1.0, 1.0), resolution),
+        weight.x,
+    );
+    return mix(top, bottom, weight.y);
+}
+
+fn sample_canvas(uv: vec2<f32>, resolution: vec2<f32>) -> vec4<f32> {
+    let offset = vec2<f32>(effects.z / resolution.x, 0.0);
+    let center = fetch(uv, resolution);
+    return vec4<f32>(
+        fetch(uv + offset, resolution).r,
+        center.g,
+        fetch(uv - offset, resolution).b,
+        center.a,
+    );
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let resolution = vec2<f32>(textureDimensions(canvas_texture));
+
+    // Effects are laid out over the visible part of the canvas, which only
+    // differs from the whole image while scrolling with a margin.
+    let view = (mesh.uv - crop.xy) / (crop.zw - crop.xy);
+
+    var centered = view * 2.0 - 1.0;
+    centered *= 1.0 + centered.yx * centered.yx * effects.x;
+    let curved = centered * 0.5 + 0.5;
+    let uv = mix(crop.xy, crop.zw, curved);
+
+    var color = sample_canvas(clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)), resolution);
+
+    // Let the letterbox show through the corners clipped by the curvature and
+    // the margin rendered for scrolling.
+    if (any(curved < vec2<f32>(0.0)) || any(curved > vec2<f32>(1.0))) {
+        return vec4<f32>(0.0);
+    }
+
+    let row = abs(sin(uv.y * resolution.y * PI));
+    color = vec4<f32>(color.rgb * mix(1.0, row, effects.y), color.a);
+
+    color = vec4<f32>(color.rgb * (1.0 - effects.w * dot(centered, centered) * 0.5), color.a);
+
+    return color;
+}
@@ -0,0 +1,158 @@
+//! Smooth scrolling of the canvas camera at window resolution.
+
+use bevy::prelude::*;
+
+use super::{CanvasCamera, CanvasConfig, CanvasHandle, CanvasMaterial, CanvasSprite};
+
+/// Canvas pixels rendered past every edge of the canvas while a
+/// [`CanvasScroll`] exists, so the subpixel offset never reveals an edge that
+/// was not rendered.
+pub const SCROLL_MARGIN: f32 = 1.0;
+
+/// Scrolls the [`CanvasCamera`] by fractional canvas pixels without
+/// resampling the art.
+///
+/// The camera is snapped to the whole pixel nearest to `position` and the
+/// remainder is applied to the [`CanvasSprite`] instead, at window
+/// resolution. Move this instead of the camera's [`Transform`], which is
+/// overwritten.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct CanvasScroll {
+    /// Camera position in canvas pixels.
+    pub position: Vec2,
+}
+
+impl CanvasScroll {
+    pub fn new(position: Vec2) -> Self {
+        Self { position }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn snap_scroll(
+    config: Res<CanvasConfig>,
+    canvas: Res<CanvasHandle>,
+    images: Res<Assets<Image>>,
+    mut cameras: Query<(&CanvasScroll, &mut Transform), With<CanvasCamera>>,
+    mut sprites: Query<
+        (&mut Transform, &Handle<CanvasMaterial>),
+        (With<CanvasSprite>, Without<CanvasCamera>),
+    >,
+    mut materials: ResMut<Assets<CanvasMaterial>>,
+) {
+    let remainder = match cameras.get_single_mut() {
+        Ok((scroll, mut transform)) => {
+            let snapped = scroll.position.round();
+            if transform.translation.xy() != snapped {
+                transform.translation.x = snapped.x;
+                transform.translation.y = snapped.y;
+            }
+            scroll.position - snapped
+        }
+        Err(_) => Vec2::ZERO,
+    };
+
+    let Some(size) = images.get(&**canvas).map(Image::size_f32) else {
+        return;
+    };
+    // Follow the actual image, which is only resized to include the margin
+    // once the config is applied.
+    let margin = ((size - config.resolution) / 2.0).max(Vec2::ZERO);
+    let resolution = size - margin * 2.0;
+
+    // The part of the canvas image inside the fixed canvas area, in UVs.
+    let crop = Vec4::new(
+        (margin.x + remainder.x) / size.x,
+        (margin.y - remainder.y) / size.y,
+        (margin.x + resolution.x + remainder.x) / size.x,
+        (margin.y + resolution.y - remainder.y) / size.y,
+    );
+
+    for (mut transform, handle) in &mut sprites {
+        if transform.translation.xy() != -remainder {
+            transform.translation.x = -remainder.x;
+            transform.translation.y = -remainder.y;
+        }
+        if materials
+            .get(handle)
+            .is_some_and(|material| material.crop != crop)
+        {
+            if let Some(material) = materials.get_mut(handle) {
+                material.crop = crop;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{canvas::CanvasScale, testing::CanvasTestApp};
+
+    use super::*;
+
+    const RESOLUTION: Vec2 = Vec2::new(320.0, 180.0);
+
+    /// Scrolls to `position` and returns the camera translation, the canvas
+    /// sprite translation and the crop of its material.
+    fn scroll_to(app: &mut CanvasTestApp, position: Vec2) -> (Vec2, Vec2, Vec4) {
+        let world = app.app.world_mut();
+        let camera = world
+            .query_filtered::<Entity, With<CanvasCamera>>()
+            .single(world);
+        world.entity_mut(camera).insert(CanvasScroll::new(position));
+        app.update();
+
+        let world = app.app.world_mut();
+        let camera = world
+            .query_filtered::<&Transform, With<CanvasCamera>>()
+            .single(world)
+            .translation
+            .xy();
+        let (sprite, handle) = world
+            .query_filtered::<(&Transform, &Handle<CanvasMaterial>), With<CanvasSprite>>()
+            .single(world);
+        let crop = world
+            .resource::<Assets<CanvasMaterial>>()
+            .get(handle)
+            .unwrap()
+            .crop;
+        (camera, sprite.translation.xy(), crop)
+    }
+
+    /// The crop showing the canvas area shifted by `remainder`, y up.
+    fn crop(remainder: Vec2) -> Vec4 {
+        let size = RESOLUTION + SCROLL_MARGIN * 2.0;
+        Vec4::new(
+            (SCROLL_MARGIN + remainder.x) / size.x,
+            (SCROLL_MARGIN - remainder.y) / size.y,
+            (SCROLL_MARGIN + RESOLUTION.x + remainder.x) / size.x,
+            (SCROLL_MARGIN + RESOLUTION.y - remainder.y) / size.y,
+        )
+    }
+
+    #[test]
+    fn fractional_positions_offset_the_sprite() {
+        let config = CanvasConfig {
+            resolution: RESOLUTION,
+            scale: CanvasScale::Integer,
+            ..default()
+        };
+        let mut app = CanvasTestApp::new(config, 960.0, 540.0);
+
+        let (camera, sprite, material_crop) = scroll_to(&mut app, Vec2::new(10.25, -3.75));
+        assert_eq!(camera, Vec2::new(10.0, -4.0));
+        assert_eq!(sprite, Vec2::new(-0.25, -0.25));
+        assert_eq!(material_crop, crop(Vec2::new(0.25, 0.25)));
+
+        let (camera, sprite, material_crop) = scroll_to(&mut app, Vec2::new(-2.25, 7.75));
+        assert_eq!(camera, Vec2::new(-2.0, 8.0));
+        assert_eq!(sprite, Vec2::new(0.25, 0.25));
+        assert_eq!(material_crop, crop(Vec2::new(-0.25, -0.25)));
+
+        // Whole pixels show the canvas area as is.
+        let (camera, sprite, material_crop) = scroll_to(&mut app, Vec2::new(5.0, 6.0));
+        assert_eq!(camera, Vec2::new(5.0, 6.0));
+        assert_eq!(sprite, Vec2::ZERO);
+        assert_eq!(material_crop, crop(Vec2::ZERO));
+    }
+}
@@ -0,0 +1,153 @@
+//! Canvases besides the primary one configured by [`CanvasConfig`], spawned as
+//! entities.
+
+use base_config::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, view::RenderLayers},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use bevy_mod_picking::prelude::*;
+
+use super::{
+    canvas_image, canvas_size, CanvasConfig, CanvasMaterial, CanvasScale, CanvasTarget,
+    WindowCamera,
+};
+
+/// A canvas with its own resolution, scaling and render layers, e.g. a higher
+/// resolution HUD over the game world or one half of a split screen.
+///
+/// Spawning an entity with this component creates the image and the camera
+/// rendering [`Canvas::layers`] into it, and turns the entity into the mesh
+/// presenting it on [`WINDOW_LAYER`]. Add a [`Name`] to tell canvases apart.
+#[derive(Component, Clone, Debug)]
+pub struct Canvas {
+    pub resolution: Vec2,
+    pub scale: CanvasScale,
+    /// Layers rendered into this canvas.
+    pub layers: RenderLayers,
+    /// Draw order on the window, the primary canvas is drawn at `-1`. Pointer
+    /// input goes to the topmost canvas under the pointer.
+    pub order: isize,
+    /// Area of the window the canvas is fit into, from `(0, 0)` at the top
+    /// left to `(1, 1)` at the bottom right.
+    pub viewport: Rect,
+    pub clear_color: ClearColorConfig,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            resolution: CANVAS_SIZE,
+            scale: CanvasScale::default(),
+            layers: RenderLayers::default(),
+            order: 0,
+            viewport: Rect::new(0.0, 0.0, 1.0, 1.0),
+            clear_color: ClearColorConfig::Custom(Color::NONE),
+        }
+    }
+}
+
+/// Camera rendering the [`Canvas`] entity it points to.
+#[derive(Component, Debug)]
+pub struct CanvasCameraOf(pub Entity);
+
+pub(super) fn spawn_canvases(
+    mut commands: Commands,
+    canvases: Query<(Entity, &Canvas), Added<Canvas>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CanvasMaterial>>,
+) {
+    for (entity, canvas) in &canvases {
+        let image = images.add(canvas_image(canvas.resolution));
+
+        commands.entity(entity).insert((
+            CanvasTarget(image.clone()),
+            WINDOW_LAYER,
+            MaterialMesh2dBundle {
+                mesh: meshes.add(Rectangle::from_size(canvas.resolution)).into(),
+                material: materials.add(CanvasMaterial::new(&default(), image.clone())),
+                ..default()
+            },
+            Pickable::IGNORE,
+        ));
+
+        commands.spawn((
+            CanvasCameraOf(entity),
+            canvas.layers.clone(),
+            Camera2dBundle {
+                camera: Camera {
+                    order: CANVAS_ORDER,
+                    target: RenderTarget::Image(image),
+                    clear_color: canvas.clear_color,
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+pub(super) fn sync_canvases(
+    mut commands: Commands,
+    canvases: Query<(Ref<Canvas>, &CanvasTarget)>,
+    mut cameras: Query<(Entity, &CanvasCameraOf, &mut Camera, &mut RenderLayers)>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mesh_handles: Query<&mut Mesh2dHandle>,
+) {
+    for (entity, CanvasCameraOf(canvas_entity), mut camera, mut layers) in &mut cameras {
+        let Ok((canvas, CanvasTarget(image))) = canvases.get(*canvas_entity) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        if !canvas.is_changed() {
+            continue;
+        }
+
+        camera.clear_color = canvas.clear_color;
+        layers.set_if_neq(canvas.layers.clone());
+
+        let Some(image) = images.get_mut(image) else {
+            continue;
+        };
+        if image.size_f32() != canvas.resolution {
+            image.resize(canvas_size(canvas.resolution));
+            if let Ok(mut mesh) = mesh_handles.get_mut(*canvas_entity) {
+                *mesh = meshes.add(Rectangle::from_size(canvas.resolution)).into();
+            }
+        }
+    }
+}
+
+pub(super) fn fit_canvases(
+    config: Res<CanvasConfig>,
+    windows: Query<&Window>,
+    cameras: Query<&Transform, With<WindowCamera>>,
+    mut canvases: Query<(&Canvas, &mut Transform), Without<WindowCamera>>,
+) {
+    let (Ok(window), Ok(camera)) = (windows.get_single(), cameras.get_single()) else {
+        return;
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+    // World units are primary canvas pixels.
+    let world_scale = config.resolve_scale(window_size.x, window_size.y);
+
+    for (canvas, mut transform) in &mut canvases {
+        let region = window_size * canvas.viewport.size();
+        let scale = world_scale / canvas.scale.resolve(canvas.resolution, region.x, region.y);
+        let offset = (window_size * canvas.viewport.center() - window_size / 2.0)
+            * Vec2::new(1.0, -1.0)
+            * world_scale;
+
+        let fitted = Transform {
+            translation: (camera.translation.xy() + offset).extend(canvas.order as f32),
+            scale: scale.extend(1.0),
+            ..default()
+        };
+        if *transform != fitted {
+            *transform = fitted;
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Provides a virtual pointer driven by a gamepad.
+
+use bevy::{
+    input::gamepad::{GamepadAxisType, GamepadButtonType},
+    prelude::*,
+    render::camera::NormalizedRenderTarget,
+};
+use bevy_mod_picking::picking_core::{
+    pointer::{InputMove, InputPress, Location, PointerButton, PointerId, Uuid},
+    PointerCoreBundle,
+};
+
+use crate::canvas::{CanvasCamera, CanvasHandle};
+
+/// The pointer moved by the gamepad stick.
+pub const GAMEPAD_POINTER: PointerId =
+    PointerId::Custom(Uuid::from_u128(0x8e4c_21d7_5b0a_4f36_9d12_c3a7_60be_f915));
+
+/// Marks a world entity the gamepad pointer snaps to. UI buttons are always
+/// snapped to.
+///
+/// Snapping is opt-in rather than to every pickable entity: sprites are
+/// pickable by default, so backgrounds and other large sprites would pull the
+/// pointer to their centers.
+#[derive(Component, Default)]
+pub struct PointerSnap;
+
+/// Tuning and state of the gamepad pointer, on its pointer entity.
+#[derive(Component, Clone, Debug)]
+pub struct GamepadPointer {
+    /// Speed in canvas pixels per second when the stick is first pushed.
+    pub speed: f32,
+    /// Speed gained per second while the stick stays pushed.
+    pub acceleration: f32,
+    pub max_speed: f32,
+    /// Stick magnitude below which the stick is at rest.
+    pub deadzone: f32,
+    /// Distance in canvas pixels from which the pointer snaps to the center of
+    /// a [`PointerSnap`] entity or button once the stick is released.
+    pub snap_radius: f32,
+    pub position: Vec2,
+    held: Option<f32>,
+}
+
+impl Default for GamepadPointer {
+    fn default() -> Self {
+        Self {
+            speed: 60.0,
+            acceleration: 240.0,
+            max_speed: 240.0,
+            deadzone: 0.2,
+            snap_radius: 24.0,
+            position: Vec2::ZERO,
+            held: None,
+        }
+    }
+}
+
+/// Spawns the gamepad pointer in the middle of the canvas.
+pub fn spawn_gamepad_pointer(
+    mut commands: Commands,
+    canvas: Res<CanvasHandle>,
+    images: Res<Assets<Image>>,
+) {
+    let position = images
+        .get(&**canvas)
+        .map_or(Vec2::ZERO, |image| image.size_f32() / 2.0);
+
+    commands.spawn((
+        PointerCoreBundle::new(GAMEPAD_POINTER),
+        GamepadPointer {
+            position,
+            ..default()
+        },
+        #[cfg(feature = "selection")]
+        bevy_mod_picking::selection::PointerMultiselect::default(),
+    ));
+}
+
+/// Moves the gamepad pointer with the left stick of any gamepad and presses
+/// it with the face buttons.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn gamepad_pick_events(
+    time: Res<Time>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    canvas: Res<CanvasHandle>,
+    images: Res<Assets<Image>>,
+    canvas_camera: Query<(&Camera, &GlobalTransform), With<CanvasCamera>>,
+    snap_targets: Query<(&GlobalTransform, Has<Node>), Or<(With<Button>, With<PointerSnap>)>>,
+    mut pointers: Query<&mut GamepadPointer>,
+    // Output
+    mut pointer_move: EventWriter<InputMove>,
+    mut pointer_presses: EventWriter<InputPress>,
+) {
+    let Ok(mut pointer) = pointers.get_single_mut() else {
+        return;
+    };
+    let Some(size) = images.get(&**canvas).map(Image::size_f32) else {
+        return;
+    };
+
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| {
+            Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or_default(),
+                // Canvas positions grow downwards.
+                -axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or_default(),
+            )
+        })
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or_default();
+
+    let last = pointer.position;
+    if stick.length() > pointer.deadzone {
+        let held = pointer.held.unwrap_or_default() + time.delta_seconds();
+        let speed = (pointer.speed + pointer.acceleration * held).min(pointer.max_speed);
+        pointer.held = Some(held);
+        pointer.position += stick.clamp_length_max(1.0) * speed * time.delta_seconds();
+    } else if pointer.held.take().is_some() {
+        let targets = snap_targets.iter().filter_map(|(transform, is_node)| {
+            if is_node {
+                Some(transform.translation().xy())
+            } else {
+                let (camera, camera_transform) = canvas_camera.get_single().ok()?;
+                camera.world_to_viewport(camera_transform, transform.translation())
+            }
+        });
+        if let Some(target) = targets
+            .filter(|target| target.distance(pointer.position) <= pointer.snap_radius)
+            .min_by(|a, b| {
+                a.distance_squared(pointer.position)
+                    .total_cmp(&b.distance_squared(pointer.position))
+            })
+        {
+            pointer.position = target;
+        }
+    }
+    pointer.position = pointer.position.clamp(Vec2::ZERO, size);
+
+    if pointer.position != last {
+        pointer_move.send(InputMove::new(
+            GAMEPAD_POINTER,
+            Location {
+                target: NormalizedRenderTarget::Image(canvas.clone()),
+                position: pointer.position,
+            },
+            pointer.position - last,
+        ));
+    }
+
+    for gamepad in gamepads.iter() {
+        for (button_type, button) in [
+            (GamepadButtonType::South, PointerButton::Primary),
+            (GamepadButtonType::East, PointerButton::Secondary),
+            (GamepadButtonType::West, PointerButton::Middle),
+        ] {
+            let gamepad_button = GamepadButton::new(gamepad, button_type);
+            if buttons.just_pressed(gamepad_button) {
+                pointer_presses.send(InputPress::new_down(GAMEPAD_POINTER, button));
+            }
+            if buttons.just_released(gamepad_button) {
+                pointer_presses.send(InputPress::new_up(GAMEPAD_POINTER, button));
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Recognizes taps, swipes and two finger gestures from touch input.
+
+use bevy::{
+    input::touch::{TouchInput, TouchPhase},
+    prelude::*,
+    utils::HashMap,
+};
+
+use super::{CanvasPicker, RetroInputPluginSettings};
+
+/// A gesture made on the canvas. Positions are in canvas pixels, growing
+/// right and down.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// A touch released quickly without moving.
+    Tap { position: Vec2 },
+    /// A second tap close to the last one, sent after its [`Gesture::Tap`].
+    DoubleTap { position: Vec2 },
+    /// A touch held in place, sent once while it is still held.
+    LongPress { position: Vec2 },
+    /// A touch released after moving quickly, from where it started.
+    Swipe {
+        position: Vec2,
+        direction: SwipeDirection,
+        /// Average speed in canvas pixels per second.
+        velocity: f32,
+    },
+    /// Two touches moved apart or together, `scale` is the ratio of their
+    /// distance to the distance in the last [`Gesture::Pinch`].
+    Pinch { center: Vec2, scale: f32 },
+    /// Two touches moved together, by `delta` since the last
+    /// [`Gesture::TwoFingerPan`].
+    TwoFingerPan { center: Vec2, delta: Vec2 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl SwipeDirection {
+    fn from_delta(delta: Vec2) -> Self {
+        if delta.x.abs() >= delta.y.abs() {
+            if delta.x < 0.0 {
+                SwipeDirection::Left
+            } else {
+                SwipeDirection::Right
+            }
+        } else if delta.y < 0.0 {
+            SwipeDirection::Up
+        } else {
+            SwipeDirection::Down
+        }
+    }
+}
+
+/// Thresholds of the gestures recognized by the
+/// [`RetroInputPlugin`](super::RetroInputPlugin), distances are in canvas
+/// pixels and times in seconds.
+#[derive(Clone, Debug, Reflect)]
+pub struct GestureSettings {
+    /// How far a touch can move and still be a tap or long press.
+    pub tap_slop: f32,
+    /// Longest a touch can be held and still be a tap.
+    pub tap_time: f32,
+    /// Longest time between two taps of a double tap.
+    pub double_tap_time: f32,
+    /// Farthest two taps of a double tap can be apart.
+    pub double_tap_distance: f32,
+    /// How long a touch is held before it is a long press.
+    pub long_press_time: f32,
+    /// Shortest distance a touch moves to be a swipe.
+    pub swipe_distance: f32,
+    /// Lowest average speed of a swipe, per second.
+    pub swipe_velocity: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_slop: 4.0,
+            tap_time: 0.3,
+            double_tap_time: 0.3,
+            double_tap_distance: 12.0,
+            long_press_time: 0.5,
+            swipe_distance: 24.0,
+            swipe_velocity: 200.0,
+        }
+    }
+}
+
+struct TrackedTouch {
+    start: Vec2,
+    position: Vec2,
+    started: f32,
+    moved: bool,
+    long_pressed: bool,
+    /// Part of a two finger gesture, so never a tap, long press or swipe.
+    multi: bool,
+}
+
+#[derive(Default)]
+pub struct GestureState {
+    touches: HashMap<u64, TrackedTouch>,
+    last_tap: Option<(f32, Vec2)>,
+    /// Distance between and center of the two touches last frame.
+    pair: Option<(f32, Vec2)>,
+}
+
+/// Sends [`Gesture`]s recognized from the touches on the canvas.
+pub fn recognize_gestures(
+    settings: Res<RetroInputPluginSettings>,
+    time: Res<Time<Real>>,
+    canvas_picker: CanvasPicker,
+    mut touches: EventReader<TouchInput>,
+    mut state: Local<GestureState>,
+    mut gestures: EventWriter<Gesture>,
+) {
+    let settings = &settings.gestures;
+    let now = time.elapsed_seconds();
+    let state = &mut *state;
+
+    for touch in touches.read() {
+        let Some(position) = canvas_picker
+            .locate(touch.position)
+            .map(|location| location.position)
+        else {
+            continue;
+        };
+
+        match touch.phase {
+            TouchPhase::Started => {
+                let multi = !state.touches.is_empty();
+                state
+                    .touches
+                    .values_mut()
+                    .for_each(|other| other.multi |= multi);
+                state.touches.insert(
+                    touch.id,
+                    TrackedTouch {
+                        start: position,
+                        position,
+                        started: now,
+                        moved: false,
+                        long_pressed: false,
+                        multi,
+                    },
+                );
+                state.pair = None;
+            }
+            TouchPhase::Moved => {
+                if let Some(tracked) = state.touches.get_mut(&touch.id) {
+                    tracked.position = position;
+                    tracked.moved |= position.distance(tracked.start) > settings.tap_slop;
+                }
+            }
+            TouchPhase::Ended => {
+                state.pair = None;
+                let Some(tracked) = state.touches.remove(&touch.id) else {
+                    continue;
+                };
+                if tracked.multi || tracked.long_pressed {
+                    continue;
+                }
+
+                let duration = now - tracked.started;
+                if !tracked.moved && duration <= settings.tap_time {
+                    gestures.send(Gesture::Tap {
+                        position: tracked.start,
+                    });
+
+                    let double = state.last_tap.is_some_and(|(time, last)| {
+                        now - time <= settings.double_tap_time
+                            && last.distance(tracked.start) <= settings.double_tap_distance
+                    });
+                    if double {
+                        gestures.send(Gesture::DoubleTap {
+                            position: tracked.start,
+                        });
+                        state.last_tap = None;
+                    } else {
+                        state.last_tap = Some((now, tracked.start));
+                    }
+                } else if tracked.moved {
+                    let delta = position - tracked.start;
+                    let velocity = delta.length() / duration.max(f32::EPSILON);
+                    if delta.length() >= settings.swipe_distance
+                        && velocity >= settings.swipe_velocity
+                    {
+                        gestures.send(Gesture::Swipe {
+                            position: tracked.start,
+                            direction: SwipeDirection::from_delta(delta),
+                            velocity,
+                        });
+                    }
+                }
+            }
+            TouchPhase::Canceled => {
+                state.touches.remove(&touch.id);
+                state.pair = None;
+            }
+        }
+    }
+
+    for tracked in state.touches.values_mut() {
+        if !tracked.multi
+            && !tracked.moved
+            && !tracked.long_pressed
+            && now - tracked.started >= settings.long_press_time
+        {
+            tracked.long_pressed = true;
+            gestures.send(Gesture::LongPress {
+                position: tracked.start,
+            });
+        }
+    }
+
+    let mut positions = state.touches.values().map(|tracked| tracked.position);
+    let (Some(a), Some(b), None) = (positions.next(), positions.next(), positions.next()) else {
+        state.pair = None;
+        return;
+    };
+    let distance = a.distance(b);
+    let center = (a + b) / 2.0;
+
+    if let Some((last_distance, last_center)) = state.pair {
+        if distance != last_distance && last_distance > 0.0 {
+            gestures.send(Gesture::Pinch {
+                center,
+                scale: distance / last_distance,
+            });
+        }
+        if center != last_center {
+            gestures.send(Gesture::TwoFingerPan {
+                center,
+                delta: center - last_center,
+            });
+        }
+    }
+    state.pair = Some((distance, center));
+}
@@ -1,6 +1,16 @@
-use bevy::prelude::*;
-use bevy_mod_picking::picking_core::PickSet;
+use bevy::{ecs::system::SystemParam, prelude::*, render::camera::NormalizedRenderTarget};
+use bevy_eventlistener::EventListenerPlugin;
+use bevy_mod_picking::picking_core::{events::Pointer, pointer::Location, PickSet};
 
+use crate::canvas::{CanvasSetup, CanvasSprite, CanvasTarget, WindowCamera};
+
+use self::{
+    gesture::{Gesture, GestureSettings},
+    mouse::{InputScroll, Navigate, NavigationAction, Scroll},
+};
+
+pub mod gamepad;
+pub mod gesture;
 pub mod mouse;
 pub mod touch;
 
@@ -8,12 +18,28 @@ pub struct RetroInputPlugin;
 impl Plugin for RetroInputPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<RetroInputPluginSettings>()
-            .add_systems(Startup, mouse::spawn_mouse_pointer)
+            .add_event::<Gesture>()
+            .add_event::<InputScroll>()
+            .add_event::<Navigate>()
+            .add_event::<Pointer<Scroll>>()
+            .add_plugins(EventListenerPlugin::<Pointer<Scroll>>::default())
+            .add_systems(
+                Startup,
+                (
+                    mouse::spawn_mouse_pointer,
+                    gamepad::spawn_gamepad_pointer
+                        .after(CanvasSetup)
+                        .run_if(RetroInputPluginSettings::is_gamepad_enabled),
+                ),
+            )
             .add_systems(
                 First,
                 (
                     touch::touch_pick_events.run_if(RetroInputPluginSettings::is_touch_enabled),
+                    gesture::recognize_gestures.run_if(RetroInputPluginSettings::is_touch_enabled),
                     mouse::mouse_pick_events.run_if(RetroInputPluginSettings::is_mouse_enabled),
+                    gamepad::gamepad_pick_events
+                        .run_if(RetroInputPluginSettings::is_gamepad_enabled),
                     // IMPORTANT: the commands must be flushed after `touch_pick_events` is run
                     // because we need pointer spawning to happen immediately to prevent issues
                     // with missed events during drag and drop.
@@ -22,6 +48,12 @@ impl Plugin for RetroInputPlugin {
                     .chain()
                     .in_set(PickSet::Input),
             )
+            .add_systems(
+                PreUpdate,
+                mouse::mouse_scroll_events
+                    .run_if(RetroInputPluginSettings::is_wheel_enabled)
+                    .in_set(PickSet::PostFocus),
+            )
             .add_systems(
                 Last,
                 touch::deactivate_touch_pointers.run_if(RetroInputPluginSettings::is_touch_enabled),
@@ -42,6 +74,15 @@ pub struct RetroInputPluginSettings {
     is_touch_enabled: bool,
     /// Should mouse inputs be updated?
     is_mouse_enabled: bool,
+    /// Should the gamepad pointer be spawned and updated?
+    is_gamepad_enabled: bool,
+    /// Should the mouse wheel send [`InputScroll`] and [`Pointer<Scroll>`]?
+    is_wheel_enabled: bool,
+    /// Thresholds of the recognized touch [`Gesture`]s.
+    pub gestures: GestureSettings,
+    /// Mouse buttons past the middle one that send [`Navigate`], others are
+    /// dropped.
+    pub extra_buttons: Vec<(MouseButton, NavigationAction)>,
 }
 
 impl Default for RetroInputPluginSettings {
@@ -49,6 +90,13 @@ impl Default for RetroInputPluginSettings {
         Self {
             is_touch_enabled: true,
             is_mouse_enabled: true,
+            is_gamepad_enabled: true,
+            is_wheel_enabled: true,
+            gestures: default(),
+            extra_buttons: vec![
+                (MouseButton::Back, NavigationAction::Back),
+                (MouseButton::Forward, NavigationAction::Forward),
+            ],
         }
     }
 }
@@ -60,4 +108,450 @@ impl RetroInputPluginSettings {
     fn is_mouse_enabled(state: Res<Self>) -> bool {
         state.is_mouse_enabled
     }
+    fn is_gamepad_enabled(state: Res<Self>) -> bool {
+        state.is_gamepad_enabled
+    }
+    fn is_wheel_enabled(state: Res<Self>) -> bool {
+        state.is_mouse_enabled && state.is_wheel_enabled
+    }
+}
+
+/// Maps window positions to the canvas drawn under them.
+#[derive(SystemParam)]
+pub struct CanvasPicker<'w, 's> {
+    window_camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<WindowCamera>>,
+    canvases: Query<
+        'w,
+        's,
+        (
+            &'static CanvasTarget,
+            &'static GlobalTransform,
+            &'static ViewVisibility,
+            Has<CanvasSprite>,
+        ),
+    >,
+    images: Res<'w, Assets<Image>>,
+}
+
+impl CanvasPicker<'_, '_> {
+    /// Returns where `position`, in logical window pixels, lands on the
+    /// topmost visible canvas under it.
+    ///
+    /// Positions outside of every canvas are mapped onto the primary canvas,
+    /// outside of its bounds, so pointers keep moving over the letterbox.
+    pub fn locate(&self, position: Vec2) -> Option<Location> {
+        let (camera, camera_transform) = self.window_camera.get_single().ok()?;
+        let world_position = camera.viewport_to_world_2d(camera_transform, position)?;
+
+        let mut primary = None;
+        let mut topmost: Option<(f32, Location)> = None;
+        for (CanvasTarget(image), transform, visibility, is_primary) in &self.canvases {
+            let Some(size) = self.images.get(image).map(Image::size_f32) else {
+                continue;
+            };
+            let local = transform
+                .affine()
+                .inverse()
+                .transform_point3(world_position.extend(0.0))
+                .xy();
+            let location = Location {
+                target: NormalizedRenderTarget::Image(image.clone()),
+                position: Vec2::new(local.x, -local.y) + size / 2.0,
+            };
+
+            let depth = transform.translation().z;
+            let inside =
+                location.position.cmpge(Vec2::ZERO).all() && location.position.cmplt(size).all();
+            if inside && visibility.get() && topmost.as_ref().is_none_or(|(top, _)| depth > *top) {
+                topmost = Some((depth, location.clone()));
+            }
+            if is_primary {
+                primary = Some(location);
+            }
+        }
+
+        topmost.map(|(_, location)| location).or(primary)
+    }
+
+    /// Returns the image and size of the primary canvas.
+    pub fn primary(&self) -> Option<(Handle<Image>, Vec2)> {
+        self.canvases
+            .iter()
+            .find(|(.., is_primary)| *is_primary)
+            .and_then(|(CanvasTarget(image), ..)| {
+                let size = self.images.get(image)?.size_f32();
+                Some((image.clone(), size))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        input::{gamepad::GamepadButtonType, mouse::MouseScrollUnit, touch::TouchPhase},
+        window::CursorGrabMode,
+    };
+    use bevy_mod_picking::picking_core::pointer::{PointerButton, PointerId, PressDirection};
+
+    use crate::{
+        canvas::{CanvasConfig, CanvasHandle, CanvasScale, CursorLockMode},
+        input::{
+            gamepad::{PointerSnap, GAMEPAD_POINTER},
+            gesture::{Gesture, SwipeDirection},
+        },
+        testing::CanvasTestApp,
+    };
+
+    use super::*;
+
+    #[track_caller]
+    fn assert_near(position: Option<Vec2>, expected: Vec2) {
+        let position = position.expect("no pointer move was sent");
+        assert!(
+            position.abs_diff_eq(expected, 1e-3),
+            "{position} is not {expected}"
+        );
+    }
+
+    /// Rounds gesture positions to whole canvas pixels and scales and speeds
+    /// to hundredths.
+    fn rounded(gestures: Vec<Gesture>) -> Vec<Gesture> {
+        let round = |value: f32| (value * 100.0).round() / 100.0;
+        gestures
+            .into_iter()
+            .map(|gesture| match gesture {
+                Gesture::Tap { position } => Gesture::Tap {
+                    position: position.round(),
+                },
+                Gesture::DoubleTap { position } => Gesture::DoubleTap {
+                    position: position.round(),
+                },
+                Gesture::LongPress { position } => Gesture::LongPress {
+                    position: position.round(),
+                },
+                Gesture::Swipe {
+                    position,
+                    direction,
+                    velocity,
+                } => Gesture::Swipe {
+                    position: position.round(),
+                    direction,
+                    velocity: round(velocity),
+                },
+                Gesture::Pinch { center, scale } => Gesture::Pinch {
+                    center: center.round(),
+                    scale: round(scale),
+                },
+                Gesture::TwoFingerPan { center, delta } => Gesture::TwoFingerPan {
+                    center: center.round(),
+                    delta: delta.round(),
+                },
+            })
+            .collect()
+    }
+
+    fn app(scale: CanvasScale, width: f32, height: f32) -> CanvasTestApp {
+        CanvasTestApp::new(
+            CanvasConfig {
+                resolution: Vec2::new(320.0, 180.0),
+                scale,
+                ..default()
+            },
+            width,
+            height,
+        )
+    }
+
+    #[test]
+    fn cursor_maps_to_canvas_pixels() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.move_cursor(Vec2::new(480.0, 270.0));
+        assert_near(app.last_position(), Vec2::new(160.0, 90.0));
+
+        app.move_cursor(Vec2::ZERO);
+        assert_near(app.last_position(), Vec2::ZERO);
+
+        app.move_cursor(Vec2::new(3.0, 6.0));
+        assert_near(app.last_position(), Vec2::new(1.0, 2.0));
+    }
+
+    #[test]
+    fn cursor_targets_the_canvas() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+        let canvas = (**app.app.world().resource::<CanvasHandle>()).clone();
+
+        app.move_cursor(Vec2::new(480.0, 270.0));
+        let moves = app.input_moves();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].pointer_id, PointerId::Mouse);
+        assert_eq!(
+            moves[0].location.target,
+            NormalizedRenderTarget::Image(canvas)
+        );
+    }
+
+    #[test]
+    fn letterbox_is_outside_of_the_canvas() {
+        let mut app = app(CanvasScale::Integer, 1000.0, 600.0);
+
+        app.move_cursor(Vec2::new(20.0, 30.0));
+        assert_near(app.last_position(), Vec2::ZERO);
+
+        app.move_cursor(Vec2::new(5.0, 15.0));
+        assert_near(app.last_position(), Vec2::new(-5.0, -5.0));
+    }
+
+    #[test]
+    fn cursor_follows_window_resizes() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.resize_window(640.0, 360.0);
+        app.move_cursor(Vec2::new(320.0, 180.0));
+        assert_near(app.last_position(), Vec2::new(160.0, 90.0));
+
+        app.move_cursor(Vec2::new(64.0, 36.0));
+        assert_near(app.last_position(), Vec2::new(32.0, 18.0));
+    }
+
+    #[test]
+    fn stretch_maps_each_axis() {
+        let mut app = app(CanvasScale::Stretch, 1280.0, 540.0);
+
+        app.move_cursor(Vec2::new(128.0, 54.0));
+        assert_near(app.last_position(), Vec2::new(32.0, 18.0));
+    }
+
+    #[test]
+    fn canvas_resize_moves_the_cursor_again() {
+        let mut app = app(CanvasScale::Manual(1.0), 640.0, 360.0);
+
+        app.move_cursor(Vec2::new(320.0, 180.0));
+        assert_near(app.last_position(), Vec2::new(160.0, 90.0));
+
+        app.config_mut().resolution = Vec2::new(160.0, 90.0);
+        // The canvas is resized after this frame's pointer input was read.
+        app.update();
+        app.update();
+        assert_near(app.last_position(), Vec2::new(80.0, 45.0));
+    }
+
+    #[test]
+    fn locked_mouse_motion_moves_the_pointer_from_where_it_was() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+        app.config_mut().cursor_lock = CursorLockMode::Locked;
+
+        app.move_cursor(Vec2::new(480.0, 270.0));
+        app.grab_cursor(CursorGrabMode::Locked);
+        app.move_mouse(Vec2::new(30.0, 15.0));
+        assert_near(app.last_position(), Vec2::new(170.0, 95.0));
+
+        // Cursor moves are ignored while locked.
+        app.move_cursor(Vec2::ZERO);
+        assert_eq!(app.last_position(), None);
+
+        app.move_mouse(Vec2::new(3000.0, -3000.0));
+        assert_near(app.last_position(), Vec2::new(320.0, 0.0));
+    }
+
+    #[test]
+    fn confined_pointer_stays_on_the_canvas() {
+        let mut app = app(CanvasScale::Integer, 1000.0, 600.0);
+        app.config_mut().cursor_lock = CursorLockMode::Confined;
+
+        app.move_cursor(Vec2::new(5.0, 15.0));
+        assert_near(app.last_position(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn wheel_scrolls_at_the_pointer_in_canvas_pixels() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.move_cursor(Vec2::new(480.0, 270.0));
+        app.scroll(MouseScrollUnit::Pixel, Vec2::new(0.0, -30.0));
+        app.scroll(MouseScrollUnit::Line, Vec2::new(0.0, 1.0));
+        let scrolls = app.input_scrolls();
+        assert_eq!(scrolls.len(), 2);
+        assert_eq!(scrolls[0].pointer_id, PointerId::Mouse);
+        assert_near(Some(scrolls[0].location.position), Vec2::new(160.0, 90.0));
+        assert_near(Some(scrolls[0].delta), Vec2::new(0.0, -10.0));
+        assert_eq!(scrolls[1].unit, MouseScrollUnit::Line);
+        assert_near(Some(scrolls[1].delta), Vec2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn extra_mouse_buttons_navigate() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.click_mouse(MouseButton::Back);
+        app.click_mouse(MouseButton::Forward);
+        app.click_mouse(MouseButton::Other(8));
+        let actions: Vec<_> = app.navigations().iter().map(|nav| nav.action).collect();
+        assert_eq!(actions, [NavigationAction::Back, NavigationAction::Forward]);
+        assert!(app.input_presses().is_empty());
+
+        app.app
+            .world_mut()
+            .resource_mut::<RetroInputPluginSettings>()
+            .extra_buttons
+            .clear();
+        app.click_mouse(MouseButton::Back);
+        assert!(app.navigations().is_empty());
+    }
+
+    #[test]
+    fn touches_map_to_canvas_pixels() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.touch(7, TouchPhase::Started, Vec2::new(480.0, 270.0));
+        let moves = app.input_moves();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].pointer_id, PointerId::Touch(7));
+        assert_near(Some(moves[0].location.position), Vec2::new(160.0, 90.0));
+
+        app.touch(7, TouchPhase::Moved, Vec2::new(960.0, 540.0));
+        assert_near(app.last_position(), Vec2::new(320.0, 180.0));
+    }
+
+    #[test]
+    fn gamepad_stick_moves_and_snaps_the_virtual_pointer() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+        app.app.world_mut().spawn((
+            PointerSnap,
+            SpatialBundle::from_transform(Transform::from_xyz(10.0, 0.0, 0.0)),
+        ));
+        let gamepad = app.connect_gamepad(0);
+
+        app.move_stick(gamepad, Vec2::X);
+        app.update();
+        app.update();
+        let moves = app.input_moves();
+        assert!(!moves.is_empty());
+        assert!(moves
+            .iter()
+            .all(|input| input.pointer_id == GAMEPAD_POINTER));
+        let position = moves.last().unwrap().location.position;
+        assert!(position.x > 160.0 && position.x < 170.0);
+        assert_eq!(position.y, 90.0);
+
+        app.move_stick(gamepad, Vec2::ZERO);
+        app.update();
+        assert_near(app.last_position(), Vec2::new(170.0, 90.0));
+    }
+
+    #[test]
+    fn gamepad_face_buttons_press_the_virtual_pointer() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+        let gamepad = app.connect_gamepad(0);
+
+        app.gamepad_button(gamepad, GamepadButtonType::South, true);
+        app.gamepad_button(gamepad, GamepadButtonType::South, false);
+        app.update();
+        let presses: Vec<_> = app
+            .input_presses()
+            .into_iter()
+            .map(|press| (press.pointer_id, press.direction, press.button))
+            .collect();
+        assert_eq!(
+            presses,
+            [
+                (
+                    GAMEPAD_POINTER,
+                    PressDirection::Down,
+                    PointerButton::Primary
+                ),
+                (GAMEPAD_POINTER, PressDirection::Up, PointerButton::Primary),
+            ]
+        );
+    }
+
+    #[test]
+    fn quick_touches_are_taps_and_double_taps() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.touch(1, TouchPhase::Started, Vec2::new(300.0, 150.0));
+        app.touch(1, TouchPhase::Ended, Vec2::new(300.0, 150.0));
+        app.touch(2, TouchPhase::Started, Vec2::new(303.0, 153.0));
+        app.touch(2, TouchPhase::Ended, Vec2::new(303.0, 153.0));
+        assert_eq!(
+            rounded(app.gestures()),
+            [
+                Gesture::Tap {
+                    position: Vec2::new(100.0, 50.0)
+                },
+                Gesture::Tap {
+                    position: Vec2::new(101.0, 51.0)
+                },
+                Gesture::DoubleTap {
+                    position: Vec2::new(101.0, 51.0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn held_touches_are_long_presses() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.touch(1, TouchPhase::Started, Vec2::new(300.0, 150.0));
+        for _ in 0..40 {
+            app.update();
+        }
+        app.touch(1, TouchPhase::Ended, Vec2::new(300.0, 150.0));
+        assert_eq!(
+            rounded(app.gestures()),
+            [Gesture::LongPress {
+                position: Vec2::new(100.0, 50.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn fast_moves_are_swipes() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.touch(1, TouchPhase::Started, Vec2::new(300.0, 300.0));
+        app.touch(1, TouchPhase::Moved, Vec2::new(300.0, 200.0));
+        app.touch(1, TouchPhase::Ended, Vec2::new(300.0, 150.0));
+        let gestures = rounded(app.gestures());
+        assert_eq!(gestures.len(), 1);
+        let Gesture::Swipe {
+            position,
+            direction,
+            velocity,
+        } = gestures[0]
+        else {
+            panic!("{:?} is not a swipe", gestures[0]);
+        };
+        assert_eq!(position, Vec2::new(100.0, 100.0));
+        assert_eq!(direction, SwipeDirection::Up);
+        assert!(velocity > 200.0);
+    }
+
+    #[test]
+    fn two_touches_pinch_and_pan() {
+        let mut app = app(CanvasScale::Integer, 960.0, 540.0);
+
+        app.touch(1, TouchPhase::Started, Vec2::new(300.0, 270.0));
+        app.touch(2, TouchPhase::Started, Vec2::new(600.0, 270.0));
+        app.update();
+        app.touch(2, TouchPhase::Moved, Vec2::new(750.0, 270.0));
+        assert_eq!(
+            rounded(app.gestures()),
+            [
+                Gesture::Pinch {
+                    center: Vec2::new(175.0, 90.0),
+                    scale: 1.5
+                },
+                Gesture::TwoFingerPan {
+                    center: Vec2::new(175.0, 90.0),
+                    delta: Vec2::new(25.0, 0.0)
+                },
+            ]
+        );
+
+        app.touch(1, TouchPhase::Ended, Vec2::new(300.0, 270.0));
+        app.touch(2, TouchPhase::Ended, Vec2::new(750.0, 270.0));
+        assert!(app.gestures().is_empty());
+    }
 }
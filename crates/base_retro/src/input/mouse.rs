@@ -1,16 +1,59 @@
 //! Provides sensible defaults for mouse picking inputs.
 
 use bevy::{
-    input::{mouse::MouseButtonInput, ButtonState},
+    input::{
+        mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
+        ButtonState,
+    },
     prelude::*,
     render::camera::NormalizedRenderTarget,
+    window::{CursorGrabMode, PrimaryWindow},
 };
-use bevy_mod_picking::picking_core::{
-    pointer::{InputMove, InputPress, Location, PointerButton, PointerId},
-    PointerCoreBundle,
+use bevy_mod_picking::{
+    backend::HitData,
+    picking_core::{
+        events::Pointer,
+        focus::HoverMap,
+        pointer::{InputMove, InputPress, Location, PointerButton, PointerId, PointerLocation},
+        PointerCoreBundle,
+    },
 };
 
-use crate::{canvas::CanvasHandle, prelude::*};
+use super::{CanvasPicker, RetroInputPluginSettings};
+use crate::canvas::{CanvasConfig, CanvasResized, CursorLockMode};
+
+/// The mouse wheel scrolled over the canvas, sent whether or not anything is
+/// under the pointer.
+#[derive(Event, Clone, Debug)]
+pub struct InputScroll {
+    pub pointer_id: PointerId,
+    pub location: Location,
+    pub unit: MouseScrollUnit,
+    /// Lines, or canvas pixels for [`MouseScrollUnit::Pixel`].
+    pub delta: Vec2,
+}
+
+/// The mouse wheel scrolled over the target, see [`InputScroll`].
+#[derive(Clone, Debug, Reflect)]
+pub struct Scroll {
+    pub unit: MouseScrollUnit,
+    pub delta: Vec2,
+    pub hit: HitData,
+}
+
+/// Back and forward navigation, sent for the extra mouse buttons in
+/// [`RetroInputPluginSettings::extra_buttons`].
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Navigate {
+    pub pointer_id: PointerId,
+    pub action: NavigationAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum NavigationAction {
+    Back,
+    Forward,
+}
 
 /// Spawns the default mouse pointer.
 pub fn spawn_mouse_pointer(mut commands: Commands) {
@@ -22,47 +65,74 @@ pub fn spawn_mouse_pointer(mut commands: Commands) {
 }
 
 /// Sends mouse pointer events to be processed by the core plugin
+///
+/// While the cursor is locked, mouse motion moves the pointer over the
+/// primary canvas from where it last was.
 #[allow(clippy::too_many_arguments)]
 pub fn mouse_pick_events(
-    canvas_camera: Query<(&Camera, &GlobalTransform), With<CanvasCamera>>,
-    window_camera: Query<(&Camera, &GlobalTransform), With<WindowCamera>>,
-    canvas: Res<CanvasHandle>,
+    canvas_picker: CanvasPicker,
+    config: Res<CanvasConfig>,
+    settings: Res<RetroInputPluginSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
     // Input
     mut cursor_moves: EventReader<CursorMoved>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut canvas_resized: EventReader<CanvasResized>,
     mut cursor_last: Local<Vec2>,
+    mut window_last: Local<Option<Vec2>>,
     mut mouse_inputs: EventReader<MouseButtonInput>,
     // Output
     mut pointer_move: EventWriter<InputMove>,
     mut pointer_presses: EventWriter<InputPress>,
+    mut navigate: EventWriter<Navigate>,
 ) {
-    let Ok((canvas_camera, canvas_transform)) = canvas_camera.get_single() else {
-        return;
-    };
-    let Ok((window_camera, window_transform)) = window_camera.get_single() else {
-        return;
-    };
+    let mut window_positions: Vec<_> = cursor_moves.read().map(|event| event.position).collect();
+    let motion: Vec2 = mouse_motion.read().map(|event| event.delta).sum();
+    // The same window position lands somewhere else on a resized canvas.
+    if canvas_resized.read().count() > 0 && window_positions.is_empty() {
+        window_positions.extend(*window_last);
+    }
 
-    for event in cursor_moves.read() {
-        let position = if let Some(world_position) =
-            window_camera.viewport_to_world_2d(window_transform, event.position)
-        {
-            if let Some(viewport_position) =
-                canvas_camera.world_to_viewport(canvas_transform, world_position.extend(0.0))
-            {
-                viewport_position
-            } else {
-                continue;
-            }
-        } else {
+    let window = windows.get_single().ok();
+    let locked = window.is_some_and(|window| window.cursor.grab_mode == CursorGrabMode::Locked);
+    let primary = canvas_picker.primary();
+
+    if let (true, Some(window), Some((canvas, size))) = (locked, window, &primary) {
+        window_positions.clear();
+        if motion != Vec2::ZERO {
+            let scale = config.resolve_scale(window.width(), window.height());
+            let position =
+                (*cursor_last + motion / window.scale_factor() * scale).clamp(Vec2::ZERO, *size);
+            let location = Location {
+                target: NormalizedRenderTarget::Image(canvas.clone()),
+                position,
+            };
+            pointer_move.send(InputMove::new(
+                PointerId::Mouse,
+                location,
+                position - *cursor_last,
+            ));
+            *cursor_last = position;
+        }
+    }
+
+    for window_position in window_positions {
+        *window_last = Some(window_position);
+        let Some(mut location) = canvas_picker.locate(window_position) else {
             continue;
         };
+        if config.cursor_lock == CursorLockMode::Confined {
+            if let Some((canvas, size)) = &primary {
+                if location.target == NormalizedRenderTarget::Image(canvas.clone()) {
+                    location.position = location.position.clamp(Vec2::ZERO, *size);
+                }
+            }
+        }
+        let position = location.position;
 
         pointer_move.send(InputMove::new(
             PointerId::Mouse,
-            Location {
-                target: NormalizedRenderTarget::Image(canvas.clone()),
-                position,
-            },
+            location,
             position - *cursor_last,
         ));
         *cursor_last = position;
@@ -73,9 +143,19 @@ pub fn mouse_pick_events(
             MouseButton::Left => PointerButton::Primary,
             MouseButton::Right => PointerButton::Secondary,
             MouseButton::Middle => PointerButton::Middle,
-            MouseButton::Other(_) => continue,
-            MouseButton::Back => continue,
-            MouseButton::Forward => continue,
+            MouseButton::Other(_) | MouseButton::Back | MouseButton::Forward => {
+                let action = settings
+                    .extra_buttons
+                    .iter()
+                    .find(|(button, _)| *button == input.button);
+                if let (Some(&(_, action)), ButtonState::Pressed) = (action, input.state) {
+                    navigate.send(Navigate {
+                        pointer_id: PointerId::Mouse,
+                        action,
+                    });
+                }
+                continue;
+            }
         };
 
         match input.state {
@@ -88,3 +168,65 @@ pub fn mouse_pick_events(
         }
     }
 }
+
+/// Sends [`InputScroll`] at the mouse pointer, and [`Pointer<Scroll>`] to the
+/// nearest entity under it.
+pub fn mouse_scroll_events(
+    config: Res<CanvasConfig>,
+    hover_map: Option<Res<HoverMap>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    // Input
+    mut wheels: EventReader<MouseWheel>,
+    // Output
+    mut input_scrolls: EventWriter<InputScroll>,
+    mut scrolls: EventWriter<Pointer<Scroll>>,
+) {
+    let Some(location) = pointers
+        .iter()
+        .find(|(id, _)| **id == PointerId::Mouse)
+        .and_then(|(_, pointer)| pointer.location.clone())
+    else {
+        wheels.clear();
+        return;
+    };
+    // Pixel deltas are physical window pixels.
+    let scale = windows.get_single().map_or(Vec2::ONE, |window| {
+        config.resolve_scale(window.width(), window.height()) / window.scale_factor()
+    });
+
+    for wheel in wheels.read() {
+        let delta = match wheel.unit {
+            MouseScrollUnit::Line => Vec2::new(wheel.x, wheel.y),
+            MouseScrollUnit::Pixel => Vec2::new(wheel.x, wheel.y) * scale,
+        };
+        input_scrolls.send(InputScroll {
+            pointer_id: PointerId::Mouse,
+            location: location.clone(),
+            unit: wheel.unit,
+            delta,
+        });
+
+        // Nothing is hovered without the picking interaction plugin.
+        let hovered = hover_map
+            .as_ref()
+            .and_then(|map| map.get(&PointerId::Mouse));
+        let nearest = hovered.and_then(|hovered| {
+            hovered
+                .iter()
+                .min_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth))
+        });
+        if let Some((&target, hit)) = nearest {
+            scrolls.send(Pointer::new(
+                PointerId::Mouse,
+                location.clone(),
+                target,
+                Scroll {
+                    unit: wheel.unit,
+                    delta,
+                    hit: hit.clone(),
+                },
+            ));
+        }
+    }
+}
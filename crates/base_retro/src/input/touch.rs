@@ -4,7 +4,6 @@ use bevy::{
     hierarchy::DespawnRecursiveExt,
     input::touch::{TouchInput, TouchPhase},
     prelude::*,
-    render::camera::NormalizedRenderTarget,
     utils::{
         tracing::{debug, info},
         HashMap, HashSet,
@@ -12,11 +11,11 @@ use bevy::{
 };
 use bevy_mod_picking::picking_core::{
     events::PointerCancel,
-    pointer::{InputMove, InputPress, Location, PointerButton, PointerId},
+    pointer::{InputMove, InputPress, PointerButton, PointerId},
     PointerCoreBundle,
 };
 
-use crate::{canvas::CanvasHandle, prelude::*};
+use super::CanvasPicker;
 
 /// Sends touch pointer events to be consumed by the core plugin
 ///
@@ -25,9 +24,7 @@ use crate::{canvas::CanvasHandle, prelude::*};
 /// needed for drag and drop.
 #[allow(clippy::too_many_arguments)]
 pub fn touch_pick_events(
-    canvas_camera: Query<(&Camera, &GlobalTransform), With<CanvasCamera>>,
-    window_camera: Query<(&Camera, &GlobalTransform), With<WindowCamera>>,
-    canvas: Res<CanvasHandle>,
+    canvas_picker: CanvasPicker,
     // Input
     mut touches: EventReader<TouchInput>,
     // Local
@@ -38,32 +35,11 @@ pub fn touch_pick_events(
     mut input_presses: EventWriter<InputPress>,
     mut cancel_events: EventWriter<PointerCancel>,
 ) {
-    let Ok((canvas_camera, canvas_transform)) = canvas_camera.get_single() else {
-        return;
-    };
-    let Ok((window_camera, window_transform)) = window_camera.get_single() else {
-        return;
-    };
-
     for touch in touches.read() {
         let pointer = PointerId::Touch(touch.id);
-        let position = if let Some(world_position) =
-            window_camera.viewport_to_world_2d(window_transform, touch.position)
-        {
-            if let Some(viewport_position) =
-                canvas_camera.world_to_viewport(canvas_transform, world_position.extend(0.0))
-            {
-                viewport_position
-            } else {
-                continue;
-            }
-        } else {
+        let Some(location) = canvas_picker.locate(touch.position) else {
             continue;
         };
-        let location = Location {
-            target: NormalizedRenderTarget::Image(canvas.clone()),
-            position,
-        };
         match touch.phase {
             TouchPhase::Started => {
                 info!("Spawning pointer {:?}", pointer);
@@ -1,9 +1,11 @@
 pub mod canvas;
 pub mod input;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub mod prelude {
     pub use crate::{
-        canvas::{CanvasCamera, CanvasPlugin, WindowCamera},
+        canvas::{Canvas, CanvasCamera, CanvasPlugin, WindowCamera},
         input::{RetroInputPlugin, RetroInputPluginSettings},
     };
 }
@@ -0,0 +1,345 @@
+//! A headless [`App`] running the [`CanvasPlugin`] and [`RetroInputPlugin`]
+//! without a GPU, to test how window input lands on the canvas.
+
+use std::time::Duration;
+
+use bevy::{
+    app::Plugins,
+    audio::AudioPlugin,
+    ecs::event::ManualEventReader,
+    gilrs::GilrsPlugin,
+    input::{
+        gamepad::{
+            GamepadAxisChangedEvent, GamepadAxisType, GamepadButtonChangedEvent, GamepadButtonType,
+            GamepadConnection, GamepadConnectionEvent, GamepadEvent, GamepadInfo,
+        },
+        mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
+        touch::{TouchInput, TouchPhase},
+        ButtonState,
+    },
+    log::LogPlugin,
+    prelude::*,
+    render::{pipelined_rendering::PipelinedRenderingPlugin, settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::{CursorGrabMode, ExitCondition, PrimaryWindow, WindowResized, WindowResolution},
+    winit::WinitPlugin,
+};
+use bevy_mod_picking::picking_core::{
+    events::PointerCancel,
+    pointer::{InputMove, InputPress},
+    CorePlugin,
+};
+
+use crate::{
+    canvas::CanvasConfig,
+    input::{
+        gesture::Gesture,
+        mouse::{InputScroll, Navigate},
+    },
+    prelude::*,
+};
+
+pub struct CanvasTestApp {
+    pub app: App,
+    move_reader: ManualEventReader<InputMove>,
+    press_reader: ManualEventReader<InputPress>,
+    gesture_reader: ManualEventReader<Gesture>,
+    scroll_reader: ManualEventReader<InputScroll>,
+    navigate_reader: ManualEventReader<Navigate>,
+    moves: Vec<InputMove>,
+    presses: Vec<InputPress>,
+    gestures: Vec<Gesture>,
+    scrolls: Vec<InputScroll>,
+    navigations: Vec<Navigate>,
+}
+
+impl CanvasTestApp {
+    /// Builds the app with `config` in a `width` by `height` window and runs
+    /// it until the canvas is set up.
+    pub fn new(config: CanvasConfig, width: f32, height: f32) -> Self {
+        Self::with_plugins(config, width, height, ())
+    }
+
+    /// Like [`CanvasTestApp::new`], also adding `plugins` that build on the
+    /// canvas and its pointers.
+    pub fn with_plugins<M>(
+        config: CanvasConfig,
+        width: f32,
+        height: f32,
+        plugins: impl Plugins<M>,
+    ) -> Self {
+        let mut app = App::new();
+
+        app.add_plugins((
+            DefaultPlugins
+                .build()
+                .disable::<WinitPlugin>()
+                .disable::<LogPlugin>()
+                .disable::<AudioPlugin>()
+                .disable::<GilrsPlugin>()
+                .disable::<PipelinedRenderingPlugin>()
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        resolution: WindowResolution::new(width, height),
+                        ..default()
+                    }),
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                }),
+            CorePlugin,
+            CanvasPlugin,
+            RetroInputPlugin,
+        ))
+        .add_plugins(plugins)
+        .add_event::<PointerCancel>()
+        .insert_resource(config)
+        // Every frame takes as long, so anything moving over time is
+        // deterministic.
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 60.0,
+        )));
+
+        app.finish();
+        app.cleanup();
+
+        let mut test = Self {
+            app,
+            move_reader: default(),
+            press_reader: default(),
+            gesture_reader: default(),
+            scroll_reader: default(),
+            navigate_reader: default(),
+            moves: default(),
+            presses: default(),
+            gestures: default(),
+            scrolls: default(),
+            navigations: default(),
+        };
+        // Cameras only know their viewport after the first frame.
+        test.update();
+        test.update();
+        test.input_moves();
+        test.input_presses();
+        test.gestures();
+        test.input_scrolls();
+        test.navigations();
+        test
+    }
+
+    /// Runs a frame, collecting the pointer events it sent before they are
+    /// dropped by later frames.
+    pub fn update(&mut self) {
+        self.app.update();
+
+        let world = self.app.world();
+        self.moves.extend(
+            self.move_reader
+                .read(world.resource::<Events<InputMove>>())
+                .cloned(),
+        );
+        self.presses.extend(
+            self.press_reader
+                .read(world.resource::<Events<InputPress>>())
+                .cloned(),
+        );
+        self.gestures.extend(
+            self.gesture_reader
+                .read(world.resource::<Events<Gesture>>())
+                .copied(),
+        );
+        self.scrolls.extend(
+            self.scroll_reader
+                .read(world.resource::<Events<InputScroll>>())
+                .cloned(),
+        );
+        self.navigations.extend(
+            self.navigate_reader
+                .read(world.resource::<Events<Navigate>>())
+                .copied(),
+        );
+    }
+
+    pub fn window(&mut self) -> Entity {
+        self.app
+            .world_mut()
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(self.app.world())
+    }
+
+    pub fn config_mut(&mut self) -> Mut<'_, CanvasConfig> {
+        self.app.world_mut().resource_mut::<CanvasConfig>()
+    }
+
+    /// Moves the cursor to `position` in logical window pixels and runs a
+    /// frame.
+    pub fn move_cursor(&mut self, position: Vec2) {
+        let window = self.window();
+        self.app.world_mut().send_event(CursorMoved {
+            window,
+            position,
+            delta: None,
+        });
+        self.update();
+    }
+
+    /// Sends raw mouse motion of `delta` physical pixels and runs a frame.
+    pub fn move_mouse(&mut self, delta: Vec2) {
+        self.app.world_mut().send_event(MouseMotion { delta });
+        self.update();
+    }
+
+    /// Presses and releases `button`, running a frame after each.
+    pub fn click_mouse(&mut self, button: MouseButton) {
+        let window = self.window();
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world_mut().send_event(MouseButtonInput {
+                button,
+                state,
+                window,
+            });
+            self.update();
+        }
+    }
+
+    /// Scrolls the mouse wheel by `delta` and runs a frame.
+    pub fn scroll(&mut self, unit: MouseScrollUnit, delta: Vec2) {
+        let window = self.window();
+        self.app.world_mut().send_event(MouseWheel {
+            unit,
+            x: delta.x,
+            y: delta.y,
+            window,
+        });
+        self.update();
+    }
+
+    /// Sets the grab mode of the window like the windowing backend would once
+    /// it grabbed the cursor.
+    pub fn grab_cursor(&mut self, mode: CursorGrabMode) {
+        let window = self.window();
+        self.app
+            .world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .cursor
+            .grab_mode = mode;
+    }
+
+    /// Sends a touch at `position` in logical window pixels and runs a frame.
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2) {
+        let window = self.window();
+        self.app.world_mut().send_event(TouchInput {
+            phase,
+            position,
+            window,
+            force: None,
+            id,
+        });
+        self.update();
+    }
+
+    /// Resizes the window like the windowing backend would and runs a frame.
+    pub fn resize_window(&mut self, width: f32, height: f32) {
+        let window = self.window();
+        self.app
+            .world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .resolution
+            .set(width, height);
+        self.app.world_mut().send_event(WindowResized {
+            window,
+            width,
+            height,
+        });
+        self.update();
+    }
+
+    /// Connects a gamepad and runs a frame.
+    pub fn connect_gamepad(&mut self, id: usize) -> Gamepad {
+        let gamepad = Gamepad::new(id);
+        self.app
+            .world_mut()
+            .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+                gamepad,
+                GamepadConnection::Connected(GamepadInfo {
+                    name: "Test gamepad".into(),
+                }),
+            )));
+        self.update();
+        gamepad
+    }
+
+    /// Tilts the left stick of `gamepad` and runs a frame.
+    pub fn move_stick(&mut self, gamepad: Gamepad, stick: Vec2) {
+        for (axis_type, value) in [
+            (GamepadAxisType::LeftStickX, stick.x),
+            (GamepadAxisType::LeftStickY, stick.y),
+        ] {
+            self.app
+                .world_mut()
+                .send_event(GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+                    gamepad, axis_type, value,
+                )));
+        }
+        self.update();
+    }
+
+    /// Presses or releases a button of `gamepad` and runs a frame.
+    pub fn gamepad_button(
+        &mut self,
+        gamepad: Gamepad,
+        button_type: GamepadButtonType,
+        pressed: bool,
+    ) {
+        self.app
+            .world_mut()
+            .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+                gamepad,
+                button_type,
+                if pressed { 1.0 } else { 0.0 },
+            )));
+        self.update();
+    }
+
+    /// Returns the pointer moves sent since the last call.
+    pub fn input_moves(&mut self) -> Vec<InputMove> {
+        std::mem::take(&mut self.moves)
+    }
+
+    /// Returns the canvas position of the last pointer move sent since the
+    /// last call.
+    pub fn last_position(&mut self) -> Option<Vec2> {
+        self.input_moves()
+            .last()
+            .map(|input| input.location.position)
+    }
+
+    /// Returns the pointer presses sent since the last call.
+    pub fn input_presses(&mut self) -> Vec<InputPress> {
+        std::mem::take(&mut self.presses)
+    }
+
+    /// Returns the gestures recognized since the last call.
+    pub fn gestures(&mut self) -> Vec<Gesture> {
+        std::mem::take(&mut self.gestures)
+    }
+
+    /// Returns the scrolls sent since the last call.
+    pub fn input_scrolls(&mut self) -> Vec<InputScroll> {
+        std::mem::take(&mut self.scrolls)
+    }
+
+    /// Returns the navigations sent since the last call.
+    pub fn navigations(&mut self) -> Vec<Navigate> {
+        std::mem::take(&mut self.navigations)
+    }
+}